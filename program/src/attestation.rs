@@ -0,0 +1,184 @@
+//! Ed25519 attestations of a campaign attestor.
+//!
+//! A campaign with an attestor, committed in the airdrop PDA seeds, requires
+//! claims to be attested: the claim transaction must verify the attestor's
+//! signature over [`Attestation::message`] with an ed25519 program
+//! instruction before the claim instruction. The claim program finds it
+//! through the instructions sysvar. Attestations expire at `expiry_slot`, an
+//! attestor revokes a claimant by not renewing it.
+use crate::error::ClaimError;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program, msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+#[cfg(not(target_os = "solana"))]
+use solana_program::instruction::Instruction;
+
+/// Length of the ed25519 instruction header: number of signatures and padding.
+const ED25519_HEADER_LEN: usize = 2;
+/// Length of the offsets of one signature in ed25519 instruction data.
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = 64;
+/// Instruction index of ed25519 offsets that refers to the ed25519
+/// instruction itself.
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Attestation of a claim, passed in the claim instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Attestation {
+    /// Compliance provider of the campaign.
+    pub attestor: Pubkey,
+    /// The attestation is valid before this slot.
+    pub expiry_slot: u64,
+}
+
+impl Attestation {
+    /// Message signed by the attestor: claimant, airdrop PDA and expiry slot.
+    /// The airdrop PDA commits to all terms of the claimed campaign, so an
+    /// attestation doesn't carry over to other campaigns of the same mint.
+    pub fn message(&self, claimant: &Pubkey, airdrop_pda: &Pubkey) -> Vec<u8> {
+        let mut message = Vec::with_capacity(72);
+        message.extend_from_slice(claimant.as_ref());
+        message.extend_from_slice(airdrop_pda.as_ref());
+        message.extend_from_slice(&self.expiry_slot.to_le_bytes());
+        message
+    }
+}
+
+/// Checks that an ed25519 program instruction before the current instruction
+/// verified a signature of `attestor` over `message`.
+pub fn verify_attestation(
+    instructions_sysvar: &AccountInfo,
+    attestor: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_sysvar)?;
+        if instruction.program_id == ed25519_program::ID
+            && verifies_signature(&instruction.data, attestor, message)
+        {
+            return Ok(());
+        }
+    }
+    msg!("Missing attestation of attestor {}.", attestor);
+    Err(ClaimError::InvalidAttestation.into())
+}
+
+/// Returns whether ed25519 instruction data contains a signature of `pubkey`
+/// over `message`. Only offsets into the ed25519 instruction itself are
+/// accepted.
+fn verifies_signature(data: &[u8], pubkey: &Pubkey, message: &[u8]) -> bool {
+    let num_signatures = data.first().copied().unwrap_or_default() as usize;
+    (0..num_signatures).any(|i| {
+        let start = ED25519_HEADER_LEN + i * ED25519_OFFSETS_LEN;
+        let Some(offsets) = data.get(start..start + ED25519_OFFSETS_LEN) else {
+            return false;
+        };
+        let offset = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+        let [_, signature_index, pubkey_offset, pubkey_index, message_offset, message_size, message_index] =
+            [0, 1, 2, 3, 4, 5, 6].map(offset);
+        if [signature_index, pubkey_index, message_index] != [CURRENT_INSTRUCTION; 3] {
+            return false;
+        }
+        let pubkey_offset = pubkey_offset as usize;
+        let message_offset = message_offset as usize;
+        data.get(pubkey_offset..pubkey_offset + ED25519_PUBKEY_LEN) == Some(pubkey.as_ref())
+            && data.get(message_offset..message_offset + message_size as usize) == Some(message)
+    })
+}
+
+/// Build the ed25519 program instruction that verifies the attestor's
+/// `signature` of an attestation for `claimant` and `airdrop_pda`. It must
+/// precede the claim instruction in the transaction.
+#[cfg(not(target_os = "solana"))]
+pub fn build_attestation_instruction(
+    attestation: &Attestation,
+    claimant: &Pubkey,
+    airdrop_pda: &Pubkey,
+    signature: &[u8; 64],
+) -> Instruction {
+    let message = attestation.message(claimant, airdrop_pda);
+    let pubkey_offset = ED25519_HEADER_LEN + ED25519_OFFSETS_LEN;
+    let signature_offset = pubkey_offset + ED25519_PUBKEY_LEN;
+    let message_offset = signature_offset + ED25519_SIGNATURE_LEN;
+
+    let mut data = vec![1, 0];
+    for offset in [
+        signature_offset as u16,
+        CURRENT_INSTRUCTION,
+        pubkey_offset as u16,
+        CURRENT_INSTRUCTION,
+        message_offset as u16,
+        message.len() as u16,
+        CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(attestation.attestor.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(&message);
+
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attestation_instruction() {
+        let attestation = Attestation {
+            attestor: Pubkey::new_unique(),
+            expiry_slot: 100,
+        };
+        let claimant = Pubkey::new_unique();
+        let airdrop_pda = Pubkey::new_unique();
+        let message = attestation.message(&claimant, &airdrop_pda);
+        let instruction =
+            build_attestation_instruction(&attestation, &claimant, &airdrop_pda, &[7; 64]);
+
+        assert!(verifies_signature(
+            &instruction.data,
+            &attestation.attestor,
+            &message
+        ));
+        assert!(!verifies_signature(&instruction.data, &claimant, &message));
+        let other_claimant = attestation.message(&airdrop_pda, &airdrop_pda);
+        assert!(!verifies_signature(
+            &instruction.data,
+            &attestation.attestor,
+            &other_claimant
+        ));
+        let other_campaign = attestation.message(&claimant, &Pubkey::new_unique());
+        assert!(!verifies_signature(
+            &instruction.data,
+            &attestation.attestor,
+            &other_campaign
+        ));
+        let expired = Attestation {
+            expiry_slot: 99,
+            ..attestation
+        }
+        .message(&claimant, &airdrop_pda);
+        assert!(!verifies_signature(
+            &instruction.data,
+            &attestation.attestor,
+            &expired
+        ));
+        // offsets into another instruction are rejected.
+        let mut data = instruction.data.clone();
+        data[4..6].copy_from_slice(&0u16.to_le_bytes());
+        assert!(!verifies_signature(&data, &attestation.attestor, &message));
+    }
+}
@@ -0,0 +1,282 @@
+//! CPI into the claim program.
+//!
+//! For programs that claim airdrops on behalf of a PDA, for example a vault or
+//! DAO whose PDA is the claimant. Build with the `no-entrypoint` feature to
+//! link this crate into another program.
+use crate::{
+    attestation::Attestation,
+    instruction::ClaimProgramInstruction,
+    state::{ClaimDecay, ClaimFee, DenylistCheck},
+};
+use light_compressed_account::compressed_account::PackedMerkleContext;
+use light_compressed_account::instruction_data::compressed_proof::CompressedProof;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    pubkey::Pubkey,
+};
+
+/// Accounts of a `Claim` instruction, in instruction order, followed by the
+/// packed tree accounts.
+#[derive(Clone)]
+pub struct ClaimCpi<'a, 'info> {
+    pub claim_program: AccountInfo<'info>,
+    pub claimant: AccountInfo<'info>,
+    pub fee_payer: AccountInfo<'info>,
+    pub associated_airdrop_pda: AccountInfo<'info>,
+    pub ctoken_cpi_authority_pda: AccountInfo<'info>,
+    pub light_system_program: AccountInfo<'info>,
+    pub registered_program_pda: AccountInfo<'info>,
+    pub noop_program: AccountInfo<'info>,
+    pub account_compression_authority: AccountInfo<'info>,
+    pub account_compression_program: AccountInfo<'info>,
+    pub ctoken_program: AccountInfo<'info>,
+    pub token_pool_pda: AccountInfo<'info>,
+    pub decompress_destination: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    /// Fee recipient of the campaign, `None` if it charges no fee.
+    pub fee_recipient: Option<AccountInfo<'info>>,
+    pub tree_accounts: &'a [AccountInfo<'info>],
+    /// Instructions sysvar, required for attested claims.
+    pub instructions_sysvar: Option<AccountInfo<'info>>,
+    /// Denylist entry PDA of the claimant, required if the campaign has a
+    /// denylist.
+    pub denylist_entry: Option<AccountInfo<'info>>,
+}
+
+/// Instruction data of a `Claim` instruction, see
+/// [`ClaimProgramInstruction::Claim`].
+#[derive(Debug, Clone)]
+pub struct ClaimArgs {
+    pub proof: Option<CompressedProof>,
+    pub root_index: u16,
+    pub merkle_context: PackedMerkleContext,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+    pub mint: Pubkey,
+    pub unlock_slot: u64,
+    pub bump_seed: u8,
+    pub fee: Option<ClaimFee>,
+    pub claim_amount: Option<u64>,
+    pub output_tree_index: u8,
+    pub attestation: Option<Attestation>,
+    pub denylist: Option<DenylistCheck>,
+    pub expiry_slot: Option<u64>,
+    pub decay: Option<ClaimDecay>,
+}
+
+impl<'info> ClaimCpi<'_, 'info> {
+    pub fn instruction(&self, args: ClaimArgs) -> Instruction {
+        let instruction_data = ClaimProgramInstruction::Claim {
+            proof: args.proof,
+            root_index: args.root_index,
+            merkle_context: args.merkle_context,
+            amount: args.amount,
+            lamports: args.lamports,
+            mint: args.mint,
+            unlock_slot: args.unlock_slot,
+            bump_seed: args.bump_seed,
+            fee: args.fee,
+            claim_amount: args.claim_amount,
+            output_tree_index: args.output_tree_index,
+            attestation: args.attestation,
+            denylist: args.denylist,
+            expiry_slot: args.expiry_slot,
+            decay: args.decay,
+        };
+        let accounts = vec![
+            AccountMeta::new(*self.claimant.key, true),
+            AccountMeta::new(*self.fee_payer.key, true),
+            AccountMeta::new_readonly(*self.associated_airdrop_pda.key, false),
+            AccountMeta::new_readonly(*self.ctoken_cpi_authority_pda.key, false),
+            AccountMeta::new_readonly(*self.light_system_program.key, false),
+            AccountMeta::new_readonly(*self.registered_program_pda.key, false),
+            AccountMeta::new_readonly(*self.noop_program.key, false),
+            AccountMeta::new_readonly(*self.account_compression_authority.key, false),
+            AccountMeta::new_readonly(*self.account_compression_program.key, false),
+            AccountMeta::new_readonly(*self.ctoken_program.key, false),
+            AccountMeta::new(*self.token_pool_pda.key, false),
+            AccountMeta::new(*self.decompress_destination.key, false),
+            AccountMeta::new_readonly(*self.token_program.key, false),
+            AccountMeta::new_readonly(*self.system_program.key, false),
+            AccountMeta::new_readonly(*self.config.key, false),
+            match &self.fee_recipient {
+                Some(fee_recipient) => AccountMeta::new(*fee_recipient.key, false),
+                None => AccountMeta::new_readonly(*self.claim_program.key, false),
+            },
+        ]
+        .into_iter()
+        .chain(
+            self.tree_accounts
+                .iter()
+                .map(|tree_account| AccountMeta::new(*tree_account.key, false)),
+        )
+        .chain(
+            self.instructions_sysvar
+                .iter()
+                .chain(&self.denylist_entry)
+                .map(|account| AccountMeta::new_readonly(*account.key, false)),
+        )
+        .collect();
+
+        Instruction {
+            program_id: *self.claim_program.key,
+            accounts,
+            data: borsh::to_vec(&instruction_data).unwrap(),
+        }
+    }
+
+    pub fn account_infos(&self) -> Vec<AccountInfo<'info>> {
+        let mut account_infos = vec![
+            self.claimant.clone(),
+            self.fee_payer.clone(),
+            self.associated_airdrop_pda.clone(),
+            self.ctoken_cpi_authority_pda.clone(),
+            self.light_system_program.clone(),
+            self.registered_program_pda.clone(),
+            self.noop_program.clone(),
+            self.account_compression_authority.clone(),
+            self.account_compression_program.clone(),
+            self.ctoken_program.clone(),
+            self.token_pool_pda.clone(),
+            self.decompress_destination.clone(),
+            self.token_program.clone(),
+            self.system_program.clone(),
+            self.config.clone(),
+            self.fee_recipient
+                .clone()
+                .unwrap_or_else(|| self.claim_program.clone()),
+        ];
+        account_infos.extend_from_slice(self.tree_accounts);
+        account_infos.extend(self.instructions_sysvar.clone());
+        account_infos.extend(self.denylist_entry.clone());
+        account_infos.push(self.claim_program.clone());
+        account_infos
+    }
+}
+
+/// Claims an airdrop whose claimant signed the calling instruction.
+pub fn claim(cpi: &ClaimCpi, args: ClaimArgs) -> ProgramResult {
+    invoke(&cpi.instruction(args), &cpi.account_infos())
+}
+
+/// Claims an airdrop whose claimant is a PDA of the calling program.
+/// `signers_seeds` must include the seeds of the claimant PDA, and of the fee
+/// payer if it is a PDA too.
+pub fn claim_signed(cpi: &ClaimCpi, args: ClaimArgs, signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+    invoke_signed(&cpi.instruction(args), &cpi.account_infos(), signers_seeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::{build_claim_and_decompress_instruction, ClaimAccounts};
+
+    #[test]
+    fn test_claim_instruction() {
+        let keys: Vec<Pubkey> = (0..18).map(|_| Pubkey::new_unique()).collect();
+        let mut lamports = [0u64; 19];
+        let mut data = vec![vec![]; 19];
+        let owner = Pubkey::default();
+        let claim_program_id = crate::id();
+        let mut infos: Vec<AccountInfo> = keys
+            .iter()
+            .chain(std::iter::once(&claim_program_id))
+            .zip(lamports.iter_mut())
+            .zip(data.iter_mut())
+            .map(|((key, lamports), data)| {
+                AccountInfo::new(key, false, false, lamports, data, &owner, false, 0)
+            })
+            .collect();
+        let claim_program = infos.pop().unwrap();
+        let tree_accounts = &infos[16..];
+        let cpi = ClaimCpi {
+            claim_program,
+            claimant: infos[0].clone(),
+            fee_payer: infos[1].clone(),
+            associated_airdrop_pda: infos[2].clone(),
+            ctoken_cpi_authority_pda: infos[3].clone(),
+            light_system_program: infos[4].clone(),
+            registered_program_pda: infos[5].clone(),
+            noop_program: infos[6].clone(),
+            account_compression_authority: infos[7].clone(),
+            account_compression_program: infos[8].clone(),
+            ctoken_program: infos[9].clone(),
+            token_pool_pda: infos[10].clone(),
+            decompress_destination: infos[11].clone(),
+            token_program: infos[12].clone(),
+            system_program: infos[13].clone(),
+            config: infos[14].clone(),
+            fee_recipient: None,
+            tree_accounts,
+            instructions_sysvar: None,
+            denylist_entry: None,
+        };
+        let accounts = ClaimAccounts {
+            claimant: keys[0],
+            fee_payer: keys[1],
+            associated_airdrop_pda: keys[2],
+            ctoken_cpi_authority_pda: keys[3],
+            light_system_program: keys[4],
+            registered_program_pda: keys[5],
+            noop_program: keys[6],
+            account_compression_authority: keys[7],
+            account_compression_program: keys[8],
+            ctoken_program: keys[9],
+            token_pool_pda: keys[10],
+            decompress_destination: keys[11],
+            token_program: keys[12],
+            system_program: keys[13],
+            config: keys[14],
+            fee_recipient: None,
+            tree_accounts: keys[16..].to_vec(),
+            output_tree_index: 1,
+        };
+        let merkle_context = PackedMerkleContext {
+            merkle_tree_pubkey_index: 0,
+            queue_pubkey_index: 1,
+            leaf_index: 3,
+            prove_by_index: false,
+        };
+        let args = ClaimArgs {
+            proof: None,
+            root_index: 2,
+            merkle_context,
+            amount: 100,
+            lamports: None,
+            mint: keys[15],
+            unlock_slot: 5,
+            bump_seed: 255,
+            fee: None,
+            claim_amount: Some(40),
+            output_tree_index: 1,
+            attestation: None,
+            denylist: None,
+            expiry_slot: None,
+            decay: None,
+        };
+        let expected = build_claim_and_decompress_instruction(
+            &accounts,
+            None,
+            2,
+            merkle_context,
+            100,
+            None,
+            keys[15],
+            5,
+            255,
+            None,
+            Some(40),
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(cpi.instruction(args), expected);
+        assert_eq!(cpi.account_infos().len(), expected.accounts.len() + 1);
+    }
+}
@@ -0,0 +1,194 @@
+//! CPIs into the compressed token program.
+use light_compressed_account::instruction_data::{
+    compressed_proof::CompressedProof, cpi_context::CompressedCpiContext,
+};
+use light_compressed_token_sdk::state::{
+    CompressedTokenInstructionDataTransfer, InputTokenDataWithContext,
+    PackedTokenTransferOutputData,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const CTOKEN_PROGRAM_ID: Pubkey = pubkey!("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m");
+
+/// Anchor discriminator of the compressed token `transfer` instruction.
+const TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+#[cfg(not(target_os = "solana"))]
+const POOL_SEED: &[u8] = b"pool";
+
+/// Derive the first token pool PDA of `mint`.
+#[cfg(not(target_os = "solana"))]
+pub fn find_token_pool_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[POOL_SEED, mint.as_ref()], &CTOKEN_PROGRAM_ID).0
+}
+
+/// Accounts of a compressed token transfer CPI.
+#[derive(Clone)]
+pub struct CtokenCpiAccounts<'a, 'info> {
+    pub fee_payer: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub cpi_authority_pda: AccountInfo<'info>,
+    pub light_system_program: AccountInfo<'info>,
+    pub registered_program_pda: AccountInfo<'info>,
+    pub noop_program: AccountInfo<'info>,
+    pub account_compression_authority: AccountInfo<'info>,
+    pub account_compression_program: AccountInfo<'info>,
+    pub self_program: AccountInfo<'info>,
+    pub token_pool_pda: AccountInfo<'info>,
+    pub decompress_destination: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// Packed state trees and queues, indexed by merkle contexts and output
+    /// merkle tree indices.
+    pub tree_accounts: &'a [AccountInfo<'info>],
+    /// CPI context of the transfer, in `tree_accounts`. The light system
+    /// program stores transfers that set the context and executes them with
+    /// the next transfer that doesn't, under its proof.
+    pub cpi_context: Option<CompressedCpiContext>,
+}
+
+/// Spends `inputs` owned by the authority, creates the compressed `outputs`
+/// and decompresses the remainder, if any, to the decompress destination.
+///
+/// Unlike the sdk `decompress` instruction this can return part of the inputs
+/// as compressed outputs in the same CPI.
+pub fn transfer_and_decompress(
+    mint: &Pubkey,
+    inputs: Vec<InputTokenDataWithContext>,
+    outputs: Vec<PackedTokenTransferOutputData>,
+    proof: Option<CompressedProof>,
+    light_cpi_accounts: &CtokenCpiAccounts,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let input_amount: u64 = inputs.iter().map(|input| input.amount).sum();
+    let output_amount: u64 = outputs.iter().map(|output| output.amount).sum();
+    let decompress_amount = input_amount
+        .checked_sub(output_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let inputs = CompressedTokenInstructionDataTransfer {
+        proof,
+        mint: *mint,
+        delegated_transfer: None,
+        input_token_data_with_context: inputs,
+        output_compressed_accounts: outputs,
+        is_compress: false,
+        compress_or_decompress_amount: (decompress_amount > 0).then_some(decompress_amount),
+        cpi_context: light_cpi_accounts.cpi_context,
+        lamports_change_account_merkle_tree_index: None,
+        with_transaction_hash: false,
+    };
+    invoke_transfer(&inputs, light_cpi_accounts, signers_seeds)
+}
+
+/// Compresses the sum of `outputs` from the decompress destination, which is
+/// an SPL token account of the authority, into the compressed `outputs`.
+pub fn compress(
+    mint: &Pubkey,
+    outputs: Vec<PackedTokenTransferOutputData>,
+    light_cpi_accounts: &CtokenCpiAccounts,
+) -> ProgramResult {
+    let compress_amount = outputs.iter().map(|output| output.amount).sum();
+
+    let inputs = CompressedTokenInstructionDataTransfer {
+        proof: None,
+        mint: *mint,
+        delegated_transfer: None,
+        input_token_data_with_context: Vec::new(),
+        output_compressed_accounts: outputs,
+        is_compress: true,
+        compress_or_decompress_amount: Some(compress_amount),
+        cpi_context: None,
+        lamports_change_account_merkle_tree_index: None,
+        with_transaction_hash: false,
+    };
+    invoke_transfer(&inputs, light_cpi_accounts, &[])
+}
+
+fn invoke_transfer(
+    inputs: &CompressedTokenInstructionDataTransfer,
+    light_cpi_accounts: &CtokenCpiAccounts,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut data = TRANSFER_DISCRIMINATOR.to_vec();
+    borsh::BorshSerialize::serialize(&borsh::to_vec(inputs)?, &mut data)?;
+
+    // Optional accounts the compressed token program doesn't need are set to
+    // its own program id.
+    let (token_pool_pda, decompress_destination, token_program) =
+        if inputs.compress_or_decompress_amount.is_some() {
+            (
+                AccountMeta::new(*light_cpi_accounts.token_pool_pda.key, false),
+                AccountMeta::new(*light_cpi_accounts.decompress_destination.key, false),
+                AccountMeta::new_readonly(*light_cpi_accounts.token_program.key, false),
+            )
+        } else {
+            (
+                AccountMeta::new_readonly(CTOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(CTOKEN_PROGRAM_ID, false),
+                AccountMeta::new_readonly(CTOKEN_PROGRAM_ID, false),
+            )
+        };
+    let mut accounts = vec![
+        AccountMeta::new(*light_cpi_accounts.fee_payer.key, true),
+        AccountMeta::new_readonly(*light_cpi_accounts.authority.key, true),
+        AccountMeta::new_readonly(*light_cpi_accounts.cpi_authority_pda.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.light_system_program.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.registered_program_pda.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.noop_program.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.account_compression_authority.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.account_compression_program.key, false),
+        AccountMeta::new_readonly(CTOKEN_PROGRAM_ID, false),
+        token_pool_pda,
+        decompress_destination,
+        token_program,
+        AccountMeta::new_readonly(*light_cpi_accounts.system_program.key, false),
+    ];
+    accounts.extend(
+        light_cpi_accounts
+            .tree_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account.key, false)),
+    );
+    let instruction = Instruction {
+        program_id: CTOKEN_PROGRAM_ID,
+        accounts,
+        data,
+    };
+
+    invoke_signed(
+        &instruction,
+        &cpi_account_infos(light_cpi_accounts),
+        signers_seeds,
+    )
+}
+
+fn cpi_account_infos<'info>(
+    light_cpi_accounts: &CtokenCpiAccounts<'_, 'info>,
+) -> Vec<AccountInfo<'info>> {
+    let mut account_infos = vec![
+        light_cpi_accounts.fee_payer.clone(),
+        light_cpi_accounts.authority.clone(),
+        light_cpi_accounts.cpi_authority_pda.clone(),
+        light_cpi_accounts.light_system_program.clone(),
+        light_cpi_accounts.registered_program_pda.clone(),
+        light_cpi_accounts.noop_program.clone(),
+        light_cpi_accounts.account_compression_authority.clone(),
+        light_cpi_accounts.account_compression_program.clone(),
+        light_cpi_accounts.self_program.clone(),
+        light_cpi_accounts.token_pool_pda.clone(),
+        light_cpi_accounts.decompress_destination.clone(),
+        light_cpi_accounts.token_program.clone(),
+        light_cpi_accounts.system_program.clone(),
+    ];
+    account_infos.extend_from_slice(light_cpi_accounts.tree_accounts);
+    account_infos
+}
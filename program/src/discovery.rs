@@ -0,0 +1,443 @@
+//! Discover the airdrops of a wallet.
+//!
+//! Airdrop PDAs are derived from (claimant, mint, unlock_slot) and the
+//! campaign terms, so a wallet cannot find its compressed tokens without
+//! knowing the campaigns they were sent under. Given a list of campaigns,
+//! either parsed from a registry file or fetched from chain by the caller,
+//! this module derives every candidate PDA and, with the `client` feature,
+//! asks the indexer for the compressed token accounts it owns.
+//!
+//! Scope:
+//!
+//! - Only token airdrops of a single claimant are discovered. Multisig
+//!   airdrops are derived from the multisig rather than a wallet, lamports
+//!   airdrops hold compressed SOL instead of tokens and grant PDAs commit to
+//!   the whole grant, so none of them can be derived from a wallet and a
+//!   campaign. Clients that know the multisig or grant derive those PDAs with
+//!   the `pda` module directly.
+//! - The indexer only lists the compressed token accounts of one owner per
+//!   request, so every candidate PDA is a separate query. At most
+//!   `BATCH_SIZE` queries run concurrently, the cost grows with the number of
+//!   campaigns.
+#[cfg(feature = "client")]
+use crate::state::Claimable;
+use crate::{
+    pda::AirdropSeeds,
+    state::{ClaimDecay, ClaimFee, ClaimSource, Denylist, EarlyUnlock, FeeAmount},
+};
+#[cfg(feature = "client")]
+use futures::future::try_join_all;
+#[cfg(feature = "client")]
+use light_client::indexer::{
+    GetCompressedTokenAccountsByOwnerOrDelegateOptions, Indexer, IndexerError, TokenAccount,
+};
+use solana_program::pubkey::Pubkey;
+use std::{path::Path, str::FromStr};
+
+/// Number of airdrop PDAs queried from the indexer concurrently.
+#[cfg(feature = "client")]
+const BATCH_SIZE: usize = 16;
+
+/// A distribution identified by its mint, unlock slot and the campaign terms
+/// committed in the airdrop PDA seeds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Campaign {
+    pub mint: Pubkey,
+    pub unlock_slot: u64,
+    pub fee: Option<ClaimFee>,
+    pub hook: Option<Pubkey>,
+    pub attestor: Option<Pubkey>,
+    pub denylist: Option<Denylist>,
+    pub expiry_slot: Option<u64>,
+    pub early_unlock: Option<EarlyUnlock>,
+    pub decay: Option<ClaimDecay>,
+}
+
+impl Campaign {
+    /// Campaign without optional terms.
+    pub fn new(mint: Pubkey, unlock_slot: u64) -> Self {
+        Self {
+            mint,
+            unlock_slot,
+            fee: None,
+            hook: None,
+            attestor: None,
+            denylist: None,
+            expiry_slot: None,
+            early_unlock: None,
+            decay: None,
+        }
+    }
+
+    /// Airdrop PDA seeds of `wallet` under this campaign.
+    pub fn seeds(&self, wallet: &Pubkey) -> AirdropSeeds {
+        AirdropSeeds::new(wallet, &self.mint, self.unlock_slot)
+            .with_fee(self.fee.as_ref())
+            .with_hook(self.hook.as_ref())
+            .with_attestor(self.attestor.as_ref())
+            .with_denylist(self.denylist.as_ref())
+            .with_expiry_slot(self.expiry_slot)
+            .with_early_unlock(self.early_unlock.as_ref())
+            .with_decay(self.decay.as_ref())
+    }
+
+    /// `GetClaimable` source of the airdrop of `wallet` under this campaign.
+    pub fn source(&self, wallet: &Pubkey) -> ClaimSource {
+        ClaimSource::Airdrop {
+            claimant: *wallet,
+            mint: self.mint,
+            unlock_slot: self.unlock_slot,
+            fee: self.fee,
+            hook: self.hook,
+            attestor: self.attestor,
+            denylist: self.denylist,
+            expiry_slot: self.expiry_slot,
+            early_unlock: self.early_unlock,
+            decay: self.decay,
+        }
+    }
+}
+
+/// Compressed tokens found for a wallet under one campaign.
+#[cfg(feature = "client")]
+#[derive(Debug, Clone)]
+pub struct DiscoveredAirdrop {
+    pub claimant: Pubkey,
+    pub campaign: Campaign,
+    pub airdrop_pda: Pubkey,
+    pub bump_seed: u8,
+    /// Sum of all compressed token accounts owned by the airdrop PDA.
+    pub amount: u64,
+    pub token_accounts: Vec<TokenAccount>,
+}
+
+#[cfg(feature = "client")]
+impl DiscoveredAirdrop {
+    /// Amount a claim decompresses at `current_slot`, net of the campaign
+    /// terms, see [`ClaimSource::claimable`].
+    pub fn claimable(&self, current_slot: u64) -> Claimable {
+        self.campaign
+            .source(&self.claimant)
+            .claimable(self.amount, current_slot)
+    }
+}
+
+/// Airdrops of a wallet, split by whether they can be claimed right now.
+#[cfg(feature = "client")]
+#[derive(Debug, Clone, Default)]
+pub struct WalletAirdrops {
+    pub claimable: Vec<DiscoveredAirdrop>,
+    pub pending: Vec<DiscoveredAirdrop>,
+    /// Airdrops whose claim window or decay ended, they can't be claimed.
+    pub expired: Vec<DiscoveredAirdrop>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DiscoveryError {
+    #[error("Invalid campaign registry entry on line {line}: {reason}")]
+    InvalidRegistryEntry { line: usize, reason: String },
+    #[error("Failed to read campaign registry: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "client")]
+    #[error("Indexer error: {0}")]
+    Indexer(#[from] IndexerError),
+}
+
+/// Parse a campaign registry.
+///
+/// The registry lists one campaign per line as `<mint> <unlock_slot>`,
+/// followed by the optional campaign terms as `<term>=<value>`:
+///
+/// - `fee=lamports:<lamports>:<recipient>` or `fee=bps:<bps>:<recipient>`
+/// - `hook=<program>` and `attestor=<attestor>`
+/// - `denylist=<authority>:<address_tree>`
+/// - `expiry_slot=<slot>`
+/// - `early_unlock=<treasury>:<penalty_bps>:<start_slot>`
+/// - `decay=<treasury>:<decay_start_slot>:<end_slot>`
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_campaign_registry(registry: &str) -> Result<Vec<Campaign>, DiscoveryError> {
+    let mut campaigns = Vec::new();
+    for (i, line) in registry.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = |reason: &str| DiscoveryError::InvalidRegistryEntry {
+            line: i + 1,
+            reason: reason.to_string(),
+        };
+        let mut fields = line.split_whitespace();
+        let mint = fields
+            .next()
+            .and_then(|mint| Pubkey::from_str(mint).ok())
+            .ok_or_else(|| invalid("expected a base58 mint"))?;
+        let unlock_slot = fields
+            .next()
+            .and_then(|slot| slot.parse::<u64>().ok())
+            .ok_or_else(|| invalid("expected an unlock slot"))?;
+        let mut campaign = Campaign::new(mint, unlock_slot);
+        for term in fields {
+            parse_term(&mut campaign, term).map_err(invalid)?;
+        }
+        campaigns.push(campaign);
+    }
+    Ok(campaigns)
+}
+
+/// Parse a `<term>=<value>` campaign term into `campaign`.
+fn parse_term(campaign: &mut Campaign, term: &str) -> Result<(), &'static str> {
+    fn parse<T: FromStr>(value: &str) -> Result<T, &'static str> {
+        value.parse().map_err(|_| "invalid campaign term value")
+    }
+
+    let (name, value) = term
+        .split_once('=')
+        .ok_or("expected a campaign term as <term>=<value>")?;
+    let values: Vec<&str> = value.split(':').collect();
+    match (name, values.as_slice()) {
+        ("fee", [kind, amount, recipient]) => {
+            let amount = match *kind {
+                "lamports" => FeeAmount::Lamports(parse(amount)?),
+                "bps" => FeeAmount::Bps(parse(amount)?),
+                _ => return Err("expected a lamports or bps fee"),
+            };
+            campaign.fee = Some(ClaimFee {
+                amount,
+                recipient: parse(recipient)?,
+            });
+        }
+        ("hook", [hook]) => campaign.hook = Some(parse(hook)?),
+        ("attestor", [attestor]) => campaign.attestor = Some(parse(attestor)?),
+        ("denylist", [authority, address_tree]) => {
+            campaign.denylist = Some(Denylist {
+                authority: parse(authority)?,
+                address_tree: parse(address_tree)?,
+            })
+        }
+        ("expiry_slot", [expiry_slot]) => campaign.expiry_slot = Some(parse(expiry_slot)?),
+        ("early_unlock", [treasury, penalty_bps, start_slot]) => {
+            campaign.early_unlock = Some(EarlyUnlock {
+                treasury: parse(treasury)?,
+                penalty_bps: parse(penalty_bps)?,
+                start_slot: parse(start_slot)?,
+            })
+        }
+        ("decay", [treasury, decay_start_slot, end_slot]) => {
+            campaign.decay = Some(ClaimDecay {
+                treasury: parse(treasury)?,
+                decay_start_slot: parse(decay_start_slot)?,
+                end_slot: parse(end_slot)?,
+            })
+        }
+        _ => return Err("unknown campaign term"),
+    }
+    Ok(())
+}
+
+/// Read and parse a campaign registry file, see [`parse_campaign_registry`].
+pub fn read_campaign_registry(path: impl AsRef<Path>) -> Result<Vec<Campaign>, DiscoveryError> {
+    parse_campaign_registry(&std::fs::read_to_string(path)?)
+}
+
+/// Derive the airdrop PDA of `wallet` for every campaign.
+///
+/// Returns (campaign, airdrop_pda, bump_seed) per campaign, duplicates removed.
+pub fn candidate_airdrop_pdas(
+    wallet: &Pubkey,
+    campaigns: &[Campaign],
+) -> Vec<(Campaign, Pubkey, u8)> {
+    let mut candidates: Vec<(Campaign, Pubkey, u8)> = Vec::with_capacity(campaigns.len());
+    for campaign in campaigns {
+        if candidates.iter().any(|(c, _, _)| c == campaign) {
+            continue;
+        }
+        let (pda, bump) = campaign.seeds(wallet).find_program_address();
+        candidates.push((*campaign, pda, bump));
+    }
+    candidates
+}
+
+/// Find the token airdrops of `wallet` under `campaigns` that it can claim now
+/// or later, see the module doc for what is not discovered.
+///
+/// Queries the indexer for the candidate airdrop PDAs in concurrent batches,
+/// one query per PDA, filtered by the campaign mint and following the
+/// pagination cursor. Campaigns without compressed tokens for the wallet are
+/// omitted.
+#[cfg(feature = "client")]
+pub async fn discover_airdrops<I: Indexer>(
+    indexer: &I,
+    wallet: &Pubkey,
+    campaigns: &[Campaign],
+    current_slot: u64,
+) -> Result<WalletAirdrops, DiscoveryError> {
+    let mut airdrops = WalletAirdrops::default();
+    for batch in candidate_airdrop_pdas(wallet, campaigns).chunks(BATCH_SIZE) {
+        let batch_token_accounts = try_join_all(batch.iter().map(|(campaign, airdrop_pda, _)| {
+            get_token_accounts(indexer, airdrop_pda, &campaign.mint)
+        }))
+        .await?;
+        for (&(campaign, airdrop_pda, bump_seed), token_accounts) in
+            batch.iter().zip(batch_token_accounts)
+        {
+            if token_accounts.is_empty() {
+                continue;
+            }
+            let amount = token_accounts.iter().map(|t| t.token.amount).sum();
+            let airdrop = DiscoveredAirdrop {
+                claimant: *wallet,
+                campaign,
+                airdrop_pda,
+                bump_seed,
+                amount,
+                token_accounts,
+            };
+            let claimable = airdrop.claimable(current_slot);
+            if claimable.amount > 0 {
+                airdrops.claimable.push(airdrop);
+            } else if claimable.next_unlock_slot.is_some() {
+                airdrops.pending.push(airdrop);
+            } else {
+                airdrops.expired.push(airdrop);
+            }
+        }
+    }
+    Ok(airdrops)
+}
+
+/// Fetches all compressed token accounts of `mint` owned by `airdrop_pda`,
+/// page by page.
+#[cfg(feature = "client")]
+async fn get_token_accounts<I: Indexer>(
+    indexer: &I,
+    airdrop_pda: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Vec<TokenAccount>, IndexerError> {
+    let mut token_accounts = Vec::new();
+    let mut cursor = None;
+    loop {
+        let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+            mint: Some(*mint),
+            cursor,
+            limit: None,
+        });
+        let page = indexer
+            .get_compressed_token_accounts_by_owner(airdrop_pda, options, None)
+            .await?
+            .value;
+        let last_page = page.items.is_empty() || page.cursor.is_none();
+        token_accounts.extend(page.items);
+        if last_page {
+            return Ok(token_accounts);
+        }
+        cursor = page.cursor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_campaign_registry() {
+        let mint = Pubkey::new_unique();
+        let registry = format!("# mint unlock_slot\n\n{} 1000\n  {}   2000  \n", mint, mint);
+        let campaigns = parse_campaign_registry(&registry).unwrap();
+        assert_eq!(
+            campaigns,
+            vec![Campaign::new(mint, 1000), Campaign::new(mint, 2000)]
+        );
+
+        let invalid = parse_campaign_registry(&format!("{}\n", mint)).unwrap_err();
+        assert!(matches!(
+            invalid,
+            DiscoveryError::InvalidRegistryEntry { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_campaign_terms() {
+        let [mint, recipient, hook, treasury] = [(); 4].map(|_| Pubkey::new_unique());
+        let registry = format!(
+            "{mint} 1000 fee=bps:100:{recipient} hook={hook} expiry_slot=5000 \
+             decay={treasury}:2000:4000 early_unlock={treasury}:500:10\n\
+             {mint} 1000 fee=lamports:5000:{recipient} attestor={hook} denylist={treasury}:{hook}\n"
+        );
+        let campaigns = parse_campaign_registry(&registry).unwrap();
+        assert_eq!(
+            campaigns[0],
+            Campaign {
+                fee: Some(ClaimFee {
+                    amount: FeeAmount::Bps(100),
+                    recipient,
+                }),
+                hook: Some(hook),
+                expiry_slot: Some(5000),
+                early_unlock: Some(EarlyUnlock {
+                    treasury,
+                    penalty_bps: 500,
+                    start_slot: 10,
+                }),
+                decay: Some(ClaimDecay {
+                    treasury,
+                    decay_start_slot: 2000,
+                    end_slot: 4000,
+                }),
+                ..Campaign::new(mint, 1000)
+            }
+        );
+        assert_eq!(
+            campaigns[1],
+            Campaign {
+                fee: Some(ClaimFee {
+                    amount: FeeAmount::Lamports(5000),
+                    recipient,
+                }),
+                attestor: Some(hook),
+                denylist: Some(Denylist {
+                    authority: treasury,
+                    address_tree: hook,
+                }),
+                ..Campaign::new(mint, 1000)
+            }
+        );
+
+        for term in [
+            "fee=bps:100",
+            "hook=x",
+            "denylist=x",
+            "unknown=1",
+            "expiry_slot",
+        ] {
+            let invalid = parse_campaign_registry(&format!("# header\n{mint} 1 {term}\n"));
+            assert!(matches!(
+                invalid.unwrap_err(),
+                DiscoveryError::InvalidRegistryEntry { line: 2, .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_candidate_airdrop_pdas() {
+        let wallet = Pubkey::new_unique();
+        let campaign = Campaign::new(Pubkey::new_unique(), 1000);
+        let with_hook = Campaign {
+            hook: Some(Pubkey::new_unique()),
+            ..campaign
+        };
+        let candidates = candidate_airdrop_pdas(&wallet, &[campaign, campaign, with_hook]);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(
+            (candidates[0].1, candidates[0].2),
+            crate::pda::find_airdrop_pda(&wallet, &campaign.mint, campaign.unlock_slot)
+        );
+        // campaigns with terms derive the PDA from all of them.
+        assert_eq!(
+            (candidates[1].1, candidates[1].2),
+            AirdropSeeds::new(&wallet, &campaign.mint, campaign.unlock_slot)
+                .with_hook(with_hook.hook.as_ref())
+                .find_program_address()
+        );
+    }
+}
@@ -9,6 +9,52 @@ pub enum ClaimError {
     TokensLocked,
     #[error("Invalid airdrop PDA provided.")]
     InvalidPDA,
+    #[error("Claims are paused.")]
+    ClaimsPaused,
+    #[error("Signer is not authorized for this action.")]
+    Unauthorized,
+    #[error("Invalid config account provided.")]
+    InvalidConfig,
+    #[error("Invalid fee recipient provided.")]
+    InvalidFeeRecipient,
+    #[error("Invalid claim fee.")]
+    InvalidFee,
+    #[error("Invalid grant.")]
+    InvalidGrant,
+    #[error("Revoke slot must not be in the past.")]
+    InvalidRevokeSlot,
+    #[error("Tokens are already unlocked.")]
+    TokensUnlocked,
+    #[error("Invalid multisig.")]
+    InvalidMultisig,
+    #[error("Not enough multisig signers.")]
+    NotEnoughSigners,
+    #[error("Invalid hook program.")]
+    InvalidHookProgram,
+    #[error("Invalid claim amount.")]
+    InvalidClaimAmount,
+    #[error("Invalid attestation.")]
+    InvalidAttestation,
+    #[error("Attestation has expired.")]
+    AttestationExpired,
+    #[error("Invalid denylist entry provided.")]
+    InvalidDenylistEntry,
+    /// Unused, claims of denied wallets fail their validity proof. Kept so
+    /// later error codes don't shift.
+    #[error("Claimant is on the denylist.")]
+    ClaimantDenied,
+    #[error("Claim window has closed.")]
+    ClaimExpired,
+    #[error("Invalid early unlock penalty.")]
+    InvalidPenalty,
+    #[error("New unlock slot must be after the current lock.")]
+    InvalidRelockSlot,
+    #[error("Invalid bonus vault provided.")]
+    InvalidBonusVault,
+    #[error("Invalid migration account provided.")]
+    InvalidMigration,
+    #[error("Migration vault holds too few new tokens.")]
+    InsufficientMigrationVault,
 }
 
 impl From<ClaimError> for ProgramError {
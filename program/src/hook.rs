@@ -0,0 +1,79 @@
+//! CPI into the claim hook program of a campaign.
+//!
+//! A hook program is invoked after the airdrop is decompressed with
+//! [`CLAIM_HOOK_DISCRIMINATOR`] followed by the borsh serialized
+//! [`ClaimHookData`] as instruction data, and the hook accounts of the claim
+//! instruction as accounts.
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    pubkey::Pubkey,
+};
+
+/// Anchor discriminator of an `on_claim` instruction.
+pub const CLAIM_HOOK_DISCRIMINATOR: [u8; 8] = [122, 131, 34, 165, 135, 239, 77, 108];
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ClaimHookData {
+    pub claimant: Pubkey,
+    pub mint: Pubkey,
+    /// Amount decompressed to the decompress destination.
+    pub amount: u64,
+    pub decompress_destination: Pubkey,
+}
+
+impl ClaimHookData {
+    pub fn data(&self) -> Vec<u8> {
+        let mut data = CLAIM_HOOK_DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(self).unwrap());
+        data
+    }
+}
+
+/// Invokes `hook_program` with the claim data. Signer and writable flags of
+/// the hook accounts are passed through from the claim instruction.
+pub fn invoke_hook<'info>(
+    hook_program: &AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    hook_data: &ClaimHookData,
+) -> ProgramResult {
+    let instruction = Instruction {
+        program_id: *hook_program.key,
+        accounts: hook_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: hook_data.data(),
+    };
+    let mut account_infos = hook_accounts.to_vec();
+    account_infos.push(hook_program.clone());
+    invoke(&instruction, &account_infos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_data() {
+        let hook_data = ClaimHookData {
+            claimant: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount: 10,
+            decompress_destination: Pubkey::new_unique(),
+        };
+        let data = hook_data.data();
+        assert_eq!(data[..8], CLAIM_HOOK_DISCRIMINATOR);
+        assert_eq!(
+            ClaimHookData::try_from_slice(&data[8..]).unwrap(),
+            hook_data
+        );
+    }
+}
@@ -1,12 +1,58 @@
+use crate::attestation::Attestation;
+use crate::state::{
+    AirdropTerms, ClaimDecay, ClaimFee, ClaimMultisig, ClaimSource, DenylistCheck,
+    DenylistEntryProof, EarlyUnlock, Grant, RelockBonus,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use light_compressed_account::compressed_account::PackedMerkleContext;
 use light_compressed_account::instruction_data::compressed_proof::CompressedProof;
 use solana_program::pubkey::Pubkey;
+use std::io::{self, Read, Write};
 
 #[cfg(not(target_os = "solana"))]
 use solana_program::instruction::{AccountMeta, Instruction};
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[cfg(not(target_os = "solana"))]
+mod packed;
+#[cfg(not(target_os = "solana"))]
+pub use packed::PackedClaimAccounts;
+
+/// Version byte that prefixes serialized instructions.
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Stable wire discriminators of [`ClaimProgramInstruction`] variants.
+/// Discriminators are never reused, new instructions take the next value.
+pub mod discriminator {
+    pub const CLAIM: u8 = 0;
+    pub const INITIALIZE_CONFIG: u8 = 1;
+    pub const SET_PAUSED: u8 = 2;
+    pub const TRANSFER_ADMIN: u8 = 3;
+    pub const ACCEPT_ADMIN: u8 = 4;
+    pub const CREATE_GRANT: u8 = 5;
+    pub const CLAIM_GRANT: u8 = 6;
+    pub const REVOKE_GRANT: u8 = 7;
+    pub const TRANSFER_BENEFICIARY: u8 = 8;
+    pub const CLAIM_MULTISIG: u8 = 9;
+    pub const CLAIM_WITH_HOOK: u8 = 10;
+    pub const GET_CLAIMABLE: u8 = 11;
+    pub const ADD_TO_DENYLIST: u8 = 12;
+    pub const REMOVE_FROM_DENYLIST: u8 = 13;
+    pub const CLAIM_BASKET: u8 = 14;
+    pub const CLAIM_LAMPORTS: u8 = 15;
+    pub const EARLY_CLAIM: u8 = 16;
+    pub const RELOCK: u8 = 17;
+    pub const MERGE: u8 = 18;
+    pub const CREATE_MIGRATION: u8 = 19;
+    pub const MIGRATE: u8 = 20;
+    pub const WITHDRAW_MIGRATION: u8 = 21;
+}
+
+/// Claim program instructions.
+///
+/// Instructions are serialized as `[INSTRUCTION_VERSION, discriminator]`
+/// followed by the borsh serialized fields. Unversioned instruction data of
+/// legacy clients is rejected.
+#[derive(Debug, Clone)]
 pub enum ClaimProgramInstruction {
     Claim {
         proof: Option<CompressedProof>,
@@ -17,96 +63,2062 @@ pub enum ClaimProgramInstruction {
         mint: Pubkey,
         unlock_slot: u64,
         bump_seed: u8,
+        /// Campaign fee, committed in the airdrop PDA seeds.
+        fee: Option<ClaimFee>,
+        /// Amount to decompress, all of `amount` if `None`. The remainder
+        /// stays compressed in the airdrop PDA.
+        claim_amount: Option<u64>,
+        /// Index of the tree account that new compressed accounts are
+        /// written to.
+        output_tree_index: u8,
+        /// Attestation by the campaign attestor, required if the airdrop PDA
+        /// commits to an attestor.
+        attestation: Option<Attestation>,
+        /// Denylist checked for the claimant, required if the airdrop PDA
+        /// commits to a denylist. `proof` then also covers the denylist
+        /// entry of the claimant.
+        denylist: Option<DenylistCheck>,
+        /// Claims are rejected from this slot on, committed in the airdrop
+        /// PDA seeds.
+        expiry_slot: Option<u64>,
+        /// Decay of the claimable amount, committed in the airdrop PDA
+        /// seeds. The forfeited share is compressed to the treasury.
+        decay: Option<ClaimDecay>,
+    },
+    /// Create the config PDA with `admin` as its admin. Signed by the upgrade
+    /// authority of the claim program.
+    InitializeConfig { admin: Pubkey },
+    /// Pause or resume claims.
+    SetPaused { paused: bool },
+    /// Propose `new_admin`, it becomes admin once it calls `AcceptAdmin`.
+    TransferAdmin { new_admin: Pubkey },
+    /// Accept a pending admin transfer.
+    AcceptAdmin,
+    /// Compress `grant.amount` tokens of the grantor to the grant PDA.
+    CreateGrant {
+        grant: Grant,
+        bump_seed: u8,
+        output_tree_index: u8,
+    },
+    /// Decompress the vested, unclaimed tokens of a grant to the employee.
+    ClaimGrant {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        grant: Grant,
+        bump_seed: u8,
+        output_tree_index: u8,
+    },
+    /// Terminate a grant at `revoke_slot`. Unvested tokens return to the
+    /// grantor. Vested tokens move to the employee's airdrop PDA unlocking at
+    /// the grant start slot, or at `revoke_slot` for tokens that only vest
+    /// after the current slot. `revoke_slot` is between the current and the
+    /// end slot.
+    RevokeGrant {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        grant: Grant,
+        bump_seed: u8,
+        revoke_slot: u64,
+        output_tree_index: u8,
+    },
+    /// Move a locked airdrop to the airdrop PDA of `new_claimant` with the
+    /// same mint, unlock slot and campaign terms. Airdrops with a denylist
+    /// can't be transferred.
+    TransferBeneficiary {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        mint: Pubkey,
+        unlock_slot: u64,
+        bump_seed: u8,
+        terms: AirdropTerms,
+        new_claimant: Pubkey,
+        output_tree_index: u8,
+    },
+    /// Claim an airdrop of an M-of-N multisig, requires `multisig.threshold`
+    /// signers.
+    ClaimMultisig {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        mint: Pubkey,
+        unlock_slot: u64,
+        bump_seed: u8,
+        fee: Option<ClaimFee>,
+        multisig: ClaimMultisig,
+        output_tree_index: u8,
+    },
+    /// Claim an airdrop and invoke the campaign hook program, committed in
+    /// the airdrop PDA seeds, with the claimed amount.
+    ClaimWithHook {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        mint: Pubkey,
+        unlock_slot: u64,
+        bump_seed: u8,
+        fee: Option<ClaimFee>,
+        hook: Pubkey,
+        output_tree_index: u8,
+    },
+    /// Write the `Claimable` amount of an airdrop or grant PDA holding
+    /// `amount` at the current slot as return data. Doesn't modify accounts.
+    GetClaimable {
+        source: ClaimSource,
+        amount: u64,
+        bump_seed: u8,
+    },
+    /// Add `address` to the denylist of the signing authority with entries
+    /// in `address_tree`. `proof` covers the entry: the non-inclusion of its
+    /// address for a new entry, the inclusion of a removed one.
+    AddToDenylist {
+        proof: Option<CompressedProof>,
+        address: Pubkey,
+        address_tree: Pubkey,
+        entry: DenylistEntryProof,
+        output_tree_index: u8,
+    },
+    /// Remove `address` from the denylist of the signing authority with
+    /// entries in `address_tree`. `proof` covers the inclusion of the entry.
+    RemoveFromDenylist {
+        proof: Option<CompressedProof>,
+        address: Pubkey,
+        address_tree: Pubkey,
+        merkle_context: PackedMerkleContext,
+        root_index: u16,
+        output_tree_index: u8,
+    },
+    /// Claim the airdrops of several mints with the same claimant and
+    /// unlock slot, each to its own decompress destination. `proof` covers
+    /// the inputs of all claims, which are transferred through the CPI
+    /// context account at `cpi_context_index` in the tree accounts.
+    ///
+    /// Each claim carries the campaign terms of its airdrop. Fee, expiry
+    /// slot, early unlock and decay terms apply as in `Claim` and
+    /// `EarlyClaim`. Hook, attestor and denylist terms are not supported,
+    /// such airdrops are claimed on their own, and are rejected.
+    ClaimBasket {
+        proof: Option<CompressedProof>,
+        unlock_slot: u64,
+        claims: Vec<BasketClaim>,
+        cpi_context_index: u8,
+        output_tree_index: u8,
+    },
+    /// Decompress the compressed SOL of a lamports airdrop PDA to the
+    /// decompress destination.
+    ClaimLamports {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        lamports: u64,
+        unlock_slot: u64,
+        bump_seed: u8,
+    },
+    /// Claim an airdrop with early unlock terms, committed in the airdrop PDA
+    /// seeds. Before the unlock slot the early unlock penalty is compressed
+    /// to the treasury and the rest is decompressed. From the unlock slot on
+    /// there is no penalty.
+    EarlyClaim {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        mint: Pubkey,
+        unlock_slot: u64,
+        bump_seed: u8,
+        fee: Option<ClaimFee>,
+        early_unlock: EarlyUnlock,
+        output_tree_index: u8,
+    },
+    /// Move an airdrop to the airdrop PDA of the same claimant, mint and fee
+    /// with the later `new_unlock_slot`, optionally with a bonus from the
+    /// bonus vault for the added lock duration.
+    Relock {
+        proof: Option<CompressedProof>,
+        root_index: u16,
+        merkle_context: PackedMerkleContext,
+        amount: u64,
+        lamports: Option<u64>,
+        mint: Pubkey,
+        unlock_slot: u64,
+        bump_seed: u8,
+        fee: Option<ClaimFee>,
+        new_unlock_slot: u64,
+        bonus: Option<RelockBonusInput>,
+        output_tree_index: u8,
+    },
+    /// Move the airdrops of `positions` to the airdrop PDA of the same
+    /// claimant, mint and fee unlocking at `unlock_slot`, after the unlock
+    /// slots of all positions.
+    Merge {
+        mint: Pubkey,
+        unlock_slot: u64,
+        bump_seed: u8,
+        fee: Option<ClaimFee>,
+        positions: Vec<MergePosition>,
+        output_tree_index: u8,
+    },
+    /// Create the migration PDA of the signing authority from `old_mint` to
+    /// `new_mint`, which releases `ratio_numerator / ratio_denominator` new
+    /// tokens per old token.
+    CreateMigration {
+        old_mint: Pubkey,
+        new_mint: Pubkey,
+        ratio_numerator: u64,
+        ratio_denominator: u64,
+        bump_seed: u8,
+    },
+    /// Escrow `amount` old tokens of the claimant with the migration PDA and
+    /// decompress the new tokens they migrate to from the `vault` account of
+    /// the migration PDA.
+    Migrate {
+        amount: u64,
+        source: MigrationSource,
+        vault: MigrationInput,
+        output_tree_index: u8,
     },
+    /// Decompress the `input` account of the migration PDA, escrowed old
+    /// tokens or unreleased new tokens of `mint`, to the migration authority.
+    /// Signed by the migration authority.
+    WithdrawMigration {
+        mint: Pubkey,
+        input: MigrationInput,
+        output_tree_index: u8,
+    },
+}
+
+/// Compressed token account of the bonus vault spent by a `Relock`
+/// instruction, see [`crate::pda::find_bonus_vault_pda`].
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RelockBonusInput {
+    pub proof: Option<CompressedProof>,
+    pub root_index: u16,
+    pub merkle_context: PackedMerkleContext,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+    pub bonus: RelockBonus,
+    pub bump_seed: u8,
+}
+
+/// Airdrop merged by a `Merge` instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MergePosition {
+    pub proof: Option<CompressedProof>,
+    pub root_index: u16,
+    pub merkle_context: PackedMerkleContext,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+    pub unlock_slot: u64,
+    pub bump_seed: u8,
+}
+
+/// Compressed token account spent by a `Migrate` instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MigrationInput {
+    pub proof: Option<CompressedProof>,
+    pub root_index: u16,
+    pub merkle_context: PackedMerkleContext,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+}
+
+/// Old tokens escrowed by a `Migrate` instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum MigrationSource {
+    /// SPL token account of the claimant, compressed to the migration PDA.
+    Spl,
+    /// Compressed token account of the claimant, transferred to the migration
+    /// PDA. The change returns to the claimant.
+    Compressed(MigrationInput),
+}
+
+/// Airdrop of one mint in a `ClaimBasket` instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct BasketClaim {
+    pub root_index: u16,
+    pub merkle_context: PackedMerkleContext,
+    pub amount: u64,
+    pub lamports: Option<u64>,
+    pub mint: Pubkey,
+    pub bump_seed: u8,
+    pub terms: AirdropTerms,
+}
+
+impl ClaimProgramInstruction {
+    pub fn discriminator(&self) -> u8 {
+        match self {
+            Self::Claim { .. } => discriminator::CLAIM,
+            Self::InitializeConfig { .. } => discriminator::INITIALIZE_CONFIG,
+            Self::SetPaused { .. } => discriminator::SET_PAUSED,
+            Self::TransferAdmin { .. } => discriminator::TRANSFER_ADMIN,
+            Self::AcceptAdmin => discriminator::ACCEPT_ADMIN,
+            Self::CreateGrant { .. } => discriminator::CREATE_GRANT,
+            Self::ClaimGrant { .. } => discriminator::CLAIM_GRANT,
+            Self::RevokeGrant { .. } => discriminator::REVOKE_GRANT,
+            Self::TransferBeneficiary { .. } => discriminator::TRANSFER_BENEFICIARY,
+            Self::ClaimMultisig { .. } => discriminator::CLAIM_MULTISIG,
+            Self::ClaimWithHook { .. } => discriminator::CLAIM_WITH_HOOK,
+            Self::GetClaimable { .. } => discriminator::GET_CLAIMABLE,
+            Self::AddToDenylist { .. } => discriminator::ADD_TO_DENYLIST,
+            Self::RemoveFromDenylist { .. } => discriminator::REMOVE_FROM_DENYLIST,
+            Self::ClaimBasket { .. } => discriminator::CLAIM_BASKET,
+            Self::ClaimLamports { .. } => discriminator::CLAIM_LAMPORTS,
+            Self::EarlyClaim { .. } => discriminator::EARLY_CLAIM,
+            Self::Relock { .. } => discriminator::RELOCK,
+            Self::Merge { .. } => discriminator::MERGE,
+            Self::CreateMigration { .. } => discriminator::CREATE_MIGRATION,
+            Self::Migrate { .. } => discriminator::MIGRATE,
+            Self::WithdrawMigration { .. } => discriminator::WITHDRAW_MIGRATION,
+        }
+    }
+}
+
+/// Deserializes an `Option` that is `None` if the reader is exhausted.
+fn deserialize_trailing_option<R: Read, T: BorshDeserialize>(
+    reader: &mut R,
+) -> io::Result<Option<T>> {
+    let mut tag = [0u8; 1];
+    if reader.read(&mut tag)? == 0 {
+        return Ok(None);
+    }
+    match tag[0] {
+        0 => Ok(None),
+        1 => Ok(Some(T::deserialize_reader(reader)?)),
+        tag => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid Option tag: {}", tag),
+        )),
+    }
+}
+
+impl BorshSerialize for ClaimProgramInstruction {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (INSTRUCTION_VERSION, self.discriminator()).serialize(writer)?;
+        match self {
+            Self::Claim {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                claim_amount,
+                output_tree_index,
+                attestation,
+                denylist,
+                expiry_slot,
+                decay,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                claim_amount,
+                output_tree_index,
+                attestation,
+                denylist,
+                expiry_slot,
+                decay,
+            )
+                .serialize(writer),
+            Self::InitializeConfig { admin } => admin.serialize(writer),
+            Self::SetPaused { paused } => paused.serialize(writer),
+            Self::TransferAdmin { new_admin } => new_admin.serialize(writer),
+            Self::AcceptAdmin => Ok(()),
+            Self::CreateGrant {
+                grant,
+                bump_seed,
+                output_tree_index,
+            } => (grant, bump_seed, output_tree_index).serialize(writer),
+            Self::ClaimGrant {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                grant,
+                bump_seed,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                grant,
+                bump_seed,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::RevokeGrant {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                grant,
+                bump_seed,
+                revoke_slot,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                grant,
+                bump_seed,
+                revoke_slot,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::TransferBeneficiary {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                terms,
+                new_claimant,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                terms,
+                new_claimant,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::ClaimMultisig {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                multisig,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                multisig,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::ClaimWithHook {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                hook,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                hook,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::GetClaimable {
+                source,
+                amount,
+                bump_seed,
+            } => (source, amount, bump_seed).serialize(writer),
+            Self::AddToDenylist {
+                proof,
+                address,
+                address_tree,
+                entry,
+                output_tree_index,
+            } => (proof, address, address_tree, entry, output_tree_index).serialize(writer),
+            Self::RemoveFromDenylist {
+                proof,
+                address,
+                address_tree,
+                merkle_context,
+                root_index,
+                output_tree_index,
+            } => (
+                proof,
+                address,
+                address_tree,
+                merkle_context,
+                root_index,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::ClaimBasket {
+                proof,
+                unlock_slot,
+                claims,
+                cpi_context_index,
+                output_tree_index,
+            } => (
+                proof,
+                unlock_slot,
+                claims,
+                cpi_context_index,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::ClaimLamports {
+                proof,
+                root_index,
+                merkle_context,
+                lamports,
+                unlock_slot,
+                bump_seed,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                lamports,
+                unlock_slot,
+                bump_seed,
+            )
+                .serialize(writer),
+            Self::EarlyClaim {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                early_unlock,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                early_unlock,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::Relock {
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                new_unlock_slot,
+                bonus,
+                output_tree_index,
+            } => (
+                proof,
+                root_index,
+                merkle_context,
+                amount,
+                lamports,
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                new_unlock_slot,
+                bonus,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::Merge {
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                positions,
+                output_tree_index,
+            } => (
+                mint,
+                unlock_slot,
+                bump_seed,
+                fee,
+                positions,
+                output_tree_index,
+            )
+                .serialize(writer),
+            Self::CreateMigration {
+                old_mint,
+                new_mint,
+                ratio_numerator,
+                ratio_denominator,
+                bump_seed,
+            } => (
+                old_mint,
+                new_mint,
+                ratio_numerator,
+                ratio_denominator,
+                bump_seed,
+            )
+                .serialize(writer),
+            Self::Migrate {
+                amount,
+                source,
+                vault,
+                output_tree_index,
+            } => (amount, source, vault, output_tree_index).serialize(writer),
+            Self::WithdrawMigration {
+                mint,
+                input,
+                output_tree_index,
+            } => (mint, input, output_tree_index).serialize(writer),
+        }
+    }
+}
+
+impl BorshDeserialize for ClaimProgramInstruction {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        match u8::deserialize_reader(reader)? {
+            INSTRUCTION_VERSION => {}
+            version => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsupported instruction version: {}", version),
+                ))
+            }
+        }
+
+        let instruction = match u8::deserialize_reader(reader)? {
+            discriminator::CLAIM => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    claim_amount,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                // claims without attestation, denylist, expiry slot or decay
+                // may omit them.
+                let attestation = deserialize_trailing_option(reader)?;
+                let denylist = deserialize_trailing_option(reader)?;
+                let expiry_slot = deserialize_trailing_option(reader)?;
+                let decay = deserialize_trailing_option(reader)?;
+                Self::Claim {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    claim_amount,
+                    output_tree_index,
+                    attestation,
+                    denylist,
+                    expiry_slot,
+                    decay,
+                }
+            }
+            discriminator::INITIALIZE_CONFIG => Self::InitializeConfig {
+                admin: BorshDeserialize::deserialize_reader(reader)?,
+            },
+            discriminator::SET_PAUSED => Self::SetPaused {
+                paused: BorshDeserialize::deserialize_reader(reader)?,
+            },
+            discriminator::TRANSFER_ADMIN => Self::TransferAdmin {
+                new_admin: BorshDeserialize::deserialize_reader(reader)?,
+            },
+            discriminator::ACCEPT_ADMIN => Self::AcceptAdmin,
+            discriminator::CREATE_GRANT => {
+                let (grant, bump_seed, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::CreateGrant {
+                    grant,
+                    bump_seed,
+                    output_tree_index,
+                }
+            }
+            discriminator::CLAIM_GRANT => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    grant,
+                    bump_seed,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::ClaimGrant {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    grant,
+                    bump_seed,
+                    output_tree_index,
+                }
+            }
+            discriminator::REVOKE_GRANT => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    grant,
+                    bump_seed,
+                    revoke_slot,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::RevokeGrant {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    grant,
+                    bump_seed,
+                    revoke_slot,
+                    output_tree_index,
+                }
+            }
+            discriminator::TRANSFER_BENEFICIARY => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    terms,
+                    new_claimant,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::TransferBeneficiary {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    terms,
+                    new_claimant,
+                    output_tree_index,
+                }
+            }
+            discriminator::CLAIM_MULTISIG => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    multisig,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::ClaimMultisig {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    multisig,
+                    output_tree_index,
+                }
+            }
+            discriminator::CLAIM_WITH_HOOK => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    hook,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::ClaimWithHook {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    hook,
+                    output_tree_index,
+                }
+            }
+            discriminator::GET_CLAIMABLE => {
+                let (source, amount, bump_seed) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::GetClaimable {
+                    source,
+                    amount,
+                    bump_seed,
+                }
+            }
+            discriminator::ADD_TO_DENYLIST => {
+                let (proof, address, address_tree, entry, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::AddToDenylist {
+                    proof,
+                    address,
+                    address_tree,
+                    entry,
+                    output_tree_index,
+                }
+            }
+            discriminator::REMOVE_FROM_DENYLIST => {
+                let (proof, address, address_tree, merkle_context, root_index, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::RemoveFromDenylist {
+                    proof,
+                    address,
+                    address_tree,
+                    merkle_context,
+                    root_index,
+                    output_tree_index,
+                }
+            }
+            discriminator::CLAIM_BASKET => {
+                let (proof, unlock_slot, claims, cpi_context_index, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::ClaimBasket {
+                    proof,
+                    unlock_slot,
+                    claims,
+                    cpi_context_index,
+                    output_tree_index,
+                }
+            }
+            discriminator::CLAIM_LAMPORTS => {
+                let (proof, root_index, merkle_context, lamports, unlock_slot, bump_seed) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::ClaimLamports {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    lamports,
+                    unlock_slot,
+                    bump_seed,
+                }
+            }
+            discriminator::EARLY_CLAIM => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    early_unlock,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::EarlyClaim {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    early_unlock,
+                    output_tree_index,
+                }
+            }
+            discriminator::RELOCK => {
+                let (
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    new_unlock_slot,
+                    bonus,
+                    output_tree_index,
+                ) = BorshDeserialize::deserialize_reader(reader)?;
+                Self::Relock {
+                    proof,
+                    root_index,
+                    merkle_context,
+                    amount,
+                    lamports,
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    new_unlock_slot,
+                    bonus,
+                    output_tree_index,
+                }
+            }
+            discriminator::MERGE => {
+                let (mint, unlock_slot, bump_seed, fee, positions, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::Merge {
+                    mint,
+                    unlock_slot,
+                    bump_seed,
+                    fee,
+                    positions,
+                    output_tree_index,
+                }
+            }
+            discriminator::CREATE_MIGRATION => {
+                let (old_mint, new_mint, ratio_numerator, ratio_denominator, bump_seed) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::CreateMigration {
+                    old_mint,
+                    new_mint,
+                    ratio_numerator,
+                    ratio_denominator,
+                    bump_seed,
+                }
+            }
+            discriminator::MIGRATE => {
+                let (amount, source, vault, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::Migrate {
+                    amount,
+                    source,
+                    vault,
+                    output_tree_index,
+                }
+            }
+            discriminator::WITHDRAW_MIGRATION => {
+                let (mint, input, output_tree_index) =
+                    BorshDeserialize::deserialize_reader(reader)?;
+                Self::WithdrawMigration {
+                    mint,
+                    input,
+                    output_tree_index,
+                }
+            }
+            discriminator => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown instruction discriminator: {}", discriminator),
+                ))
+            }
+        };
+        Ok(instruction)
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug)]
+pub struct ClaimAccounts {
+    pub claimant: Pubkey,
+    pub fee_payer: Pubkey,
+    pub associated_airdrop_pda: Pubkey,
+    pub ctoken_cpi_authority_pda: Pubkey,
+    pub light_system_program: Pubkey,
+    pub registered_program_pda: Pubkey,
+    pub noop_program: Pubkey,
+    pub account_compression_authority: Pubkey,
+    pub account_compression_program: Pubkey,
+    pub ctoken_program: Pubkey,
+    pub token_pool_pda: Pubkey,
+    pub decompress_destination: Pubkey,
+    pub token_program: Pubkey,
+    pub system_program: Pubkey,
+    pub config: Pubkey,
+    /// Required if the campaign charges a fee.
+    pub fee_recipient: Option<Pubkey>,
+    /// Packed state trees and queues, indexed by the input merkle context and
+    /// `output_tree_index`. A v1 state tree has its nullifier queue, a
+    /// batched state tree its output queue. The program reads tree accounts
+    /// up to the highest referenced index, see [`PackedClaimAccounts`].
+    pub tree_accounts: Vec<Pubkey>,
+    /// Index in `tree_accounts` that new compressed accounts are written to,
+    /// a v1 state tree or a batched output queue.
+    pub output_tree_index: u8,
+}
+
+/// Build a claim instruction in the client.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer]` Claimant
+///   1. `[signer]` Fee payer
+///   2. `[]` Associated airdrop PDA
+///   3. `[]` CToken CPI authority PDA
+///   4. `[]` Light system program
+///   5. `[]` Registered program PDA
+///   6. `[]` Noop program
+///   7. `[]` Account compression authority
+///   8. `[]` Account compression program
+///   9. `[]` CToken program
+///  10. `[]` Token pool PDA
+///  11. `[writable]` Decompress destination
+///  12. `[]` Token program
+///  13. `[]` System program
+///  14. `[]` Config PDA
+///  15. `[writable]` Fee recipient, the claim program id if there is no fee
+///  16. `[writable]` Tree accounts, any number: packed state trees and
+///      queues, v1 or batched
+///  17. `[]` Instructions sysvar, if `attestation` is set
+///  18. `[]` Claim program CPI authority PDA, if `denylist` is set
+///  19. `[]` Claim program, if `denylist` is set
+///
+/// An attested claim must be preceded by the ed25519 instruction of
+/// [`crate::attestation::build_attestation_instruction`].
+///
+/// With a `denylist`, the tree accounts also hold the CPI context account of
+/// the input state tree and, for an absent entry, the address tree of the
+/// denylist. `proof` covers the token input and the denylist entry of the
+/// claimant.
+///
+/// Inputs in a batched state tree with `merkle_context.prove_by_index` don't
+/// need a validity proof, `proof` is `None` if all inputs prove by index.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_claim_and_decompress_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    claim_amount: Option<u64>,
+    attestation: Option<Attestation>,
+    denylist: Option<DenylistCheck>,
+    expiry_slot: Option<u64>,
+    decay: Option<ClaimDecay>,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::Claim {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        claim_amount,
+        output_tree_index: accounts.output_tree_index,
+        attestation,
+        denylist,
+        expiry_slot,
+        decay,
+    };
+
+    let mut account_metas = claim_account_metas(accounts);
+    if attestation.is_some() {
+        account_metas.push(AccountMeta::new_readonly(
+            solana_program::sysvar::instructions::ID,
+            false,
+        ));
+    }
+    if denylist.is_some() {
+        account_metas.extend([
+            AccountMeta::new_readonly(crate::light_system::find_cpi_authority_pda().0, false),
+            AccountMeta::new_readonly(crate::id(), false),
+        ]);
+    }
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+fn claim_account_metas(accounts: &ClaimAccounts) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(accounts.claimant, true),
+        AccountMeta::new(accounts.fee_payer, true),
+        AccountMeta::new_readonly(accounts.associated_airdrop_pda, false),
+        AccountMeta::new_readonly(accounts.ctoken_cpi_authority_pda, false),
+        AccountMeta::new_readonly(accounts.light_system_program, false),
+        AccountMeta::new_readonly(accounts.registered_program_pda, false),
+        AccountMeta::new_readonly(accounts.noop_program, false),
+        AccountMeta::new_readonly(accounts.account_compression_authority, false),
+        AccountMeta::new_readonly(accounts.account_compression_program, false),
+        AccountMeta::new_readonly(accounts.ctoken_program, false),
+        AccountMeta::new(accounts.token_pool_pda, false),
+        AccountMeta::new(accounts.decompress_destination, false),
+        AccountMeta::new_readonly(accounts.token_program, false),
+        AccountMeta::new_readonly(accounts.system_program, false),
+        AccountMeta::new_readonly(accounts.config, false),
+        match accounts.fee_recipient {
+            Some(fee_recipient) => AccountMeta::new(fee_recipient, false),
+            None => AccountMeta::new_readonly(crate::id(), false),
+        },
+    ]
+    .into_iter()
+    .chain(
+        accounts
+            .tree_accounts
+            .iter()
+            .map(|tree_account| AccountMeta::new(*tree_account, false)),
+    )
+    .collect()
+}
+
+/// Build an instruction that compresses `grant.amount` tokens from the
+/// grantor's token account to the grant PDA.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with the grantor as claimant, the grant PDA as associated airdrop PDA and
+/// the grantor's token account as decompress destination.
+#[cfg(not(target_os = "solana"))]
+pub fn build_create_grant_instruction(
+    accounts: &ClaimAccounts,
+    grant: Grant,
+    bump_seed: u8,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::CreateGrant {
+        grant,
+        bump_seed,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that decompresses the vested, unclaimed tokens of a
+/// grant. The input compressed token account holds the grant PDA balance.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with the employee as claimant and the grant PDA as associated airdrop PDA.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_claim_grant_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    grant: Grant,
+    bump_seed: u8,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::ClaimGrant {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        grant,
+        bump_seed,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that revokes a grant at `revoke_slot`.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with the grantor as claimant and the grant PDA as associated airdrop PDA.
+/// Nothing is decompressed.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_revoke_grant_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    grant: Grant,
+    bump_seed: u8,
+    revoke_slot: u64,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::RevokeGrant {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        grant,
+        bump_seed,
+        revoke_slot,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that creates the config PDA.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer, writable]` Upgrade authority of the claim program, pays for
+///      the config PDA
+///   1. `[writable]` Config PDA
+///   2. `[]` System program
+///   3. `[]` Program data account of the claim program
+#[cfg(not(target_os = "solana"))]
+pub fn build_initialize_config_instruction(
+    upgrade_authority: Pubkey,
+    admin: Pubkey,
+) -> Instruction {
+    let (config, _) = crate::pda::find_config_pda();
+    let instruction_data = ClaimProgramInstruction::InitializeConfig { admin };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(upgrade_authority, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+            AccountMeta::new_readonly(
+                solana_program::bpf_loader_upgradeable::get_program_data_address(&crate::id()),
+                false,
+            ),
+        ],
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that moves a locked airdrop with the campaign `terms`
+/// from the claimant to `new_claimant`. Only possible before `unlock_slot` and
+/// for airdrops without a denylist.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`].
+/// Nothing is decompressed.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_transfer_beneficiary_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    terms: AirdropTerms,
+    new_claimant: Pubkey,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::TransferBeneficiary {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        terms,
+        new_claimant,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a claim instruction for an airdrop of an M-of-N multisig.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with a signing multisig member as claimant. The other signing multisig
+/// members follow the tree accounts as `[signer]` accounts.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_claim_multisig_instruction(
+    accounts: &ClaimAccounts,
+    other_signers: &[Pubkey],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    multisig: ClaimMultisig,
+) -> Instruction {
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas.extend(
+        other_signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(*signer, true)),
+    );
+    let instruction_data = ClaimProgramInstruction::ClaimMultisig {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        multisig,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a claim instruction for an airdrop with early unlock terms.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`].
+/// Before the unlock slot, the early unlock penalty is compressed to
+/// `early_unlock.treasury` in the output tree.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_early_claim_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    early_unlock: EarlyUnlock,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::EarlyClaim {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        early_unlock,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: claim_account_metas(accounts),
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that relocks an airdrop until `new_unlock_slot`.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// the decompress destination is unused. If `bonus` is set, the tree accounts
+/// are followed by the `[]` bonus vault PDA and the `[signer]` bonus funder,
+/// and must include the trees of its input.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_relock_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    new_unlock_slot: u64,
+    bonus: Option<RelockBonusInput>,
+) -> Instruction {
+    let mut account_metas = claim_account_metas(accounts);
+    if let Some(bonus) = &bonus {
+        let (bonus_vault_pda, _) = crate::pda::find_bonus_vault_pda(&mint, &bonus.bonus);
+        account_metas.push(AccountMeta::new_readonly(bonus_vault_pda, false));
+        account_metas.push(AccountMeta::new_readonly(bonus.bonus.funder, true));
+    }
+    let instruction_data = ClaimProgramInstruction::Relock {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        new_unlock_slot,
+        bonus,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that merges the airdrops of `positions` into the
+/// airdrop PDA of the claimant unlocking at `unlock_slot`. Each position
+/// carries the validity proof of its input.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with the merged airdrop PDA as associated airdrop PDA and an unused
+/// decompress destination, followed by the `[]` airdrop PDA of each
+/// position. The airdrop PDAs are derived from the claimant, mint and fee.
+#[cfg(not(target_os = "solana"))]
+pub fn build_merge_instruction(
+    accounts: &ClaimAccounts,
+    mint: Pubkey,
+    unlock_slot: u64,
+    fee: Option<ClaimFee>,
+    positions: Vec<MergePosition>,
+) -> Instruction {
+    let airdrop_pda = |unlock_slot| {
+        crate::pda::AirdropSeeds::new(&accounts.claimant, &mint, unlock_slot)
+            .with_fee(fee.as_ref())
+            .find_program_address()
+    };
+    let (associated_airdrop_pda, bump_seed) = airdrop_pda(unlock_slot);
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas[2] = AccountMeta::new_readonly(associated_airdrop_pda, false);
+    account_metas.extend(
+        positions
+            .iter()
+            .map(|position| AccountMeta::new_readonly(airdrop_pda(position.unlock_slot).0, false)),
+    );
+
+    let instruction_data = ClaimProgramInstruction::Merge {
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        positions,
+        output_tree_index: accounts.output_tree_index,
+    };
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that creates the migration PDA of `authority` from
+/// `old_mint` to `new_mint`. Fund it by compressing new tokens to the
+/// migration PDA.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer, writable]` Migration authority, pays for the migration PDA
+///   1. `[writable]` Migration PDA
+///   2. `[]` System program
+#[cfg(not(target_os = "solana"))]
+pub fn build_create_migration_instruction(
+    authority: Pubkey,
+    old_mint: Pubkey,
+    new_mint: Pubkey,
+    ratio_numerator: u64,
+    ratio_denominator: u64,
+) -> Instruction {
+    let (migration_pda, bump_seed) =
+        crate::pda::find_migration_pda(&authority, &old_mint, &new_mint);
+    let instruction_data = ClaimProgramInstruction::CreateMigration {
+        old_mint,
+        new_mint,
+        ratio_numerator,
+        ratio_denominator,
+        bump_seed,
+    };
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(migration_pda, false),
+            AccountMeta::new_readonly(solana_program::system_program::ID, false),
+        ],
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that migrates `amount` old tokens of the claimant with
+/// the migration of `authority`.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with the `[writable]` migration PDA as associated airdrop PDA and the token
+/// pool PDA and decompress destination of the new mint. For an SPL `source`
+/// the tree accounts are followed by the `[writable]` token pool PDA of the
+/// old mint and the `[writable]` `old_token_account` of the claimant.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_migrate_instruction(
+    accounts: &ClaimAccounts,
+    authority: Pubkey,
+    old_mint: Pubkey,
+    new_mint: Pubkey,
+    amount: u64,
+    source: MigrationSource,
+    vault: MigrationInput,
+    old_token_account: Option<Pubkey>,
+) -> Instruction {
+    let (migration_pda, _) = crate::pda::find_migration_pda(&authority, &old_mint, &new_mint);
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas[2] = AccountMeta::new(migration_pda, false);
+    if source == MigrationSource::Spl {
+        let old_token_account =
+            old_token_account.expect("SPL migrations require the old token account");
+        account_metas.extend([
+            AccountMeta::new(crate::ctoken::find_token_pool_pda(&old_mint), false),
+            AccountMeta::new(old_token_account, false),
+        ]);
+    }
+
+    let instruction_data = ClaimProgramInstruction::Migrate {
+        amount,
+        source,
+        vault,
+        output_tree_index: accounts.output_tree_index,
+    };
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that withdraws the `input` account of `mint` from the
+/// migration of `authority` to the decompress destination.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with `authority` as claimant, the migration PDA as associated airdrop PDA
+/// and the token pool PDA and decompress destination of `mint`, the old or
+/// the new mint.
+#[cfg(not(target_os = "solana"))]
+pub fn build_withdraw_migration_instruction(
+    accounts: &ClaimAccounts,
+    old_mint: Pubkey,
+    new_mint: Pubkey,
+    mint: Pubkey,
+    input: MigrationInput,
+) -> Instruction {
+    let (migration_pda, _) =
+        crate::pda::find_migration_pda(&accounts.claimant, &old_mint, &new_mint);
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas[2] = AccountMeta::new_readonly(migration_pda, false);
+
+    let instruction_data = ClaimProgramInstruction::WithdrawMigration {
+        mint,
+        input,
+        output_tree_index: accounts.output_tree_index,
+    };
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build a claim instruction for an airdrop with a hook program.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`].
+/// The tree accounts are followed by the `[]` hook program and the hook
+/// accounts, which are passed to the hook program as is.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_claim_with_hook_instruction(
+    accounts: &ClaimAccounts,
+    hook_accounts: &[AccountMeta],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    hook: Pubkey,
+) -> Instruction {
+    let mut account_metas = claim_account_metas(accounts);
+    account_metas.push(AccountMeta::new_readonly(hook, false));
+    account_metas.extend_from_slice(hook_accounts);
+    let instruction_data = ClaimProgramInstruction::ClaimWithHook {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        hook,
+        output_tree_index: accounts.output_tree_index,
+    };
+
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Build an instruction that claims the airdrops of several mints with the
+/// same claimant and unlock slot. `proof` is a single validity proof of the
+/// inputs of all claims.
+///
+/// Accounts are the same as for [`build_claim_and_decompress_instruction`],
+/// with the airdrop PDA, token pool PDA and decompress destination of the
+/// first claim. The tree accounts are followed by the `[writable]`
+/// `cpi_context` account of the state tree, then for each further claim:
+///
+///   0. `[]` Associated airdrop PDA
+///   1. `[writable]` Token pool PDA
+///   2. `[writable]` Decompress destination
+///
+/// The airdrop PDA and token pool PDA of each claim are derived from its
+/// mint, `decompress_destinations` are the token accounts of the claims.
+#[cfg(not(target_os = "solana"))]
+pub fn build_claim_basket_instruction(
+    accounts: &ClaimAccounts,
+    proof: Option<CompressedProof>,
+    unlock_slot: u64,
+    claims: Vec<BasketClaim>,
+    cpi_context: Pubkey,
+    decompress_destinations: &[Pubkey],
+) -> Instruction {
+    assert_eq!(claims.len(), decompress_destinations.len());
+    let mut mint_accounts =
+        claims
+            .iter()
+            .zip(decompress_destinations)
+            .map(|(claim, decompress_destination)| {
+                let (associated_airdrop_pda, _) =
+                    crate::pda::AirdropSeeds::new(&accounts.claimant, &claim.mint, unlock_slot)
+                        .with_terms(&claim.terms)
+                        .find_program_address();
+                [
+                    AccountMeta::new_readonly(associated_airdrop_pda, false),
+                    AccountMeta::new(crate::ctoken::find_token_pool_pda(&claim.mint), false),
+                    AccountMeta::new(*decompress_destination, false),
+                ]
+            });
+
+    let mut account_metas = claim_account_metas(accounts);
+    if let Some([associated_airdrop_pda, token_pool_pda, decompress_destination]) =
+        mint_accounts.next()
+    {
+        account_metas[2] = associated_airdrop_pda;
+        account_metas[10] = token_pool_pda;
+        account_metas[11] = decompress_destination;
+    }
+    account_metas.push(AccountMeta::new(cpi_context, false));
+    account_metas.extend(mint_accounts.flatten());
+
+    let instruction_data = ClaimProgramInstruction::ClaimBasket {
+        proof,
+        unlock_slot,
+        claims,
+        cpi_context_index: accounts.tree_accounts.len() as u8,
+        output_tree_index: accounts.output_tree_index,
+    };
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
 }
 
 #[cfg(not(target_os = "solana"))]
 #[derive(Debug)]
-pub struct ClaimAccounts {
+pub struct ClaimLamportsAccounts {
     pub claimant: Pubkey,
     pub fee_payer: Pubkey,
     pub associated_airdrop_pda: Pubkey,
-    pub ctoken_cpi_authority_pda: Pubkey,
     pub light_system_program: Pubkey,
     pub registered_program_pda: Pubkey,
     pub noop_program: Pubkey,
     pub account_compression_authority: Pubkey,
     pub account_compression_program: Pubkey,
-    pub ctoken_program: Pubkey,
-    pub token_pool_pda: Pubkey,
+    pub sol_pool_pda: Pubkey,
     pub decompress_destination: Pubkey,
-    pub token_program: Pubkey,
     pub system_program: Pubkey,
-    pub state_tree: Pubkey,
-    pub queue: Pubkey,
+    pub config: Pubkey,
+    /// Packed state trees and queues, indexed by the input merkle context.
+    pub tree_accounts: Vec<Pubkey>,
 }
 
-/// Build a claim instruction in the client.
+/// Build an instruction that airdrops `lamports` of `payer` as compressed SOL
+/// to the lamports airdrop PDA of `accounts.claimant` for `unlock_slot`. The
+/// compressed SOL account is created in the first tree account, a v1 state
+/// tree.
+#[cfg(not(target_os = "solana"))]
+pub fn build_airdrop_lamports_instruction(
+    accounts: &ClaimLamportsAccounts,
+    payer: Pubkey,
+    lamports: u64,
+    unlock_slot: u64,
+) -> Instruction {
+    let (associated_airdrop_pda, _) =
+        crate::pda::find_lamports_airdrop_pda(&accounts.claimant, unlock_slot);
+    crate::light_system::build_compress_lamports_instruction(
+        payer,
+        associated_airdrop_pda,
+        lamports,
+        accounts.registered_program_pda,
+        accounts.noop_program,
+        accounts.account_compression_authority,
+        accounts.account_compression_program,
+        accounts.tree_accounts[0],
+    )
+}
+
+/// Build an instruction that decompresses the compressed SOL account of a
+/// lamports airdrop PDA holding `lamports`.
 ///
 /// Accounts expected by this instruction:
 ///
 ///   0. `[signer]` Claimant
-///   1. `[signer]` Fee payer
+///   1. `[signer, writable]` Fee payer
 ///   2. `[]` Associated airdrop PDA
-///   3. `[]` CToken CPI authority PDA
-///   4. `[]` Light system program
-///   5. `[]` Registered program PDA
-///   6. `[]` Noop program
-///   7. `[]` Account compression authority
-///   8. `[]` Account compression program
-///   9. `[]` CToken program
-///  10. `[]` Token pool PDA
-///  11. `[writable]` Decompress destination
-///  12. `[]` Token program
-///  13. `[]` System program
-///  14. `[writable]` State tree
-///  15. `[writable]` Queue
+///   3. `[]` Light system program
+///   4. `[]` Registered program PDA
+///   5. `[]` Noop program
+///   6. `[]` Account compression authority
+///   7. `[]` Account compression program
+///   8. `[writable]` Sol pool PDA
+///   9. `[writable]` Decompress destination
+///  10. `[]` System program
+///  11. `[]` Config PDA
+///  12. `[writable]` Tree accounts, up to the highest index of
+///      `merkle_context`
 #[cfg(not(target_os = "solana"))]
 #[allow(clippy::too_many_arguments)]
-pub fn build_claim_and_decompress_instruction(
-    accounts: &ClaimAccounts,
+pub fn build_claim_lamports_instruction(
+    accounts: &ClaimLamportsAccounts,
     proof: Option<CompressedProof>,
     root_index: u16,
     merkle_context: PackedMerkleContext,
-    amount: u64,
-    lamports: Option<u64>,
-    mint: Pubkey,
+    lamports: u64,
     unlock_slot: u64,
     bump_seed: u8,
 ) -> Instruction {
-    let accounts = vec![
-        AccountMeta::new(accounts.claimant, true),
+    let instruction_data = ClaimProgramInstruction::ClaimLamports {
+        proof,
+        root_index,
+        merkle_context,
+        lamports,
+        unlock_slot,
+        bump_seed,
+    };
+
+    let mut account_metas = vec![
+        AccountMeta::new_readonly(accounts.claimant, true),
         AccountMeta::new(accounts.fee_payer, true),
         AccountMeta::new_readonly(accounts.associated_airdrop_pda, false),
-        AccountMeta::new_readonly(accounts.ctoken_cpi_authority_pda, false),
         AccountMeta::new_readonly(accounts.light_system_program, false),
         AccountMeta::new_readonly(accounts.registered_program_pda, false),
         AccountMeta::new_readonly(accounts.noop_program, false),
         AccountMeta::new_readonly(accounts.account_compression_authority, false),
         AccountMeta::new_readonly(accounts.account_compression_program, false),
-        AccountMeta::new_readonly(accounts.ctoken_program, false),
-        AccountMeta::new(accounts.token_pool_pda, false),
+        AccountMeta::new(accounts.sol_pool_pda, false),
         AccountMeta::new(accounts.decompress_destination, false),
-        AccountMeta::new_readonly(accounts.token_program, false),
         AccountMeta::new_readonly(accounts.system_program, false),
-        AccountMeta::new(accounts.state_tree, false),
-        AccountMeta::new(accounts.queue, false),
+        AccountMeta::new_readonly(accounts.config, false),
     ];
+    account_metas.extend(
+        accounts
+            .tree_accounts
+            .iter()
+            .map(|tree_account| AccountMeta::new(*tree_account, false)),
+    );
+    Instruction {
+        program_id: crate::id(),
+        accounts: account_metas,
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
 
-    let instruction_data = ClaimProgramInstruction::Claim {
-        proof,
-        root_index,
-        merkle_context,
+/// Build an instruction that returns the `Claimable` amount of an airdrop or
+/// grant PDA holding `amount`. Simulate it or invoke it via CPI and read the
+/// return data with `Claimable::try_from_return_data`.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[]` Associated airdrop PDA or grant PDA
+///   1. `[]` Config PDA
+#[cfg(not(target_os = "solana"))]
+pub fn build_get_claimable_instruction(
+    associated_airdrop_pda: Pubkey,
+    source: ClaimSource,
+    amount: u64,
+    bump_seed: u8,
+) -> Instruction {
+    let instruction_data = ClaimProgramInstruction::GetClaimable {
+        source,
         amount,
-        lamports,
-        mint,
-        unlock_slot,
         bump_seed,
     };
 
     Instruction {
         program_id: crate::id(),
-        accounts,
+        accounts: vec![
+            AccountMeta::new_readonly(associated_airdrop_pda, false),
+            AccountMeta::new_readonly(crate::pda::find_config_pda().0, false),
+        ],
+        data: borsh::to_vec(&instruction_data).unwrap(),
+    }
+}
+
+/// Accounts of the denylist instructions of `authority`.
+#[cfg(not(target_os = "solana"))]
+#[derive(Debug)]
+pub struct DenylistAccounts {
+    /// Denylist authority, pays for the Light system CPI.
+    pub authority: Pubkey,
+    pub registered_program_pda: Pubkey,
+    pub noop_program: Pubkey,
+    pub account_compression_authority: Pubkey,
+    pub account_compression_program: Pubkey,
+    /// Packed address tree, state trees and queues, indexed by the entry
+    /// proof and `output_tree_index`.
+    pub tree_accounts: Vec<Pubkey>,
+    /// Index in `tree_accounts` that the entry is written to.
+    pub output_tree_index: u8,
+}
+
+/// Build an instruction that adds `address` to the denylist of
+/// `accounts.authority` with entries in `address_tree`.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer, writable]` Denylist authority, pays for the entry
+///   1. `[]` Claim program CPI authority PDA
+///   2. `[]` Light system program
+///   3. `[]` Registered program PDA
+///   4. `[]` Noop program
+///   5. `[]` Account compression authority
+///   6. `[]` Account compression program
+///   7. `[]` Claim program
+///   8. `[]` System program
+///   9. `[writable]` Tree accounts, any number
+#[cfg(not(target_os = "solana"))]
+pub fn build_add_to_denylist_instruction(
+    accounts: &DenylistAccounts,
+    proof: Option<CompressedProof>,
+    address: Pubkey,
+    address_tree: Pubkey,
+    entry: DenylistEntryProof,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: denylist_account_metas(accounts),
+        data: borsh::to_vec(&ClaimProgramInstruction::AddToDenylist {
+            proof,
+            address,
+            address_tree,
+            entry,
+            output_tree_index: accounts.output_tree_index,
+        })
+        .unwrap(),
+    }
+}
+
+/// Build an instruction that removes `address` from the denylist of
+/// `accounts.authority` with entries in `address_tree`.
+///
+/// Accounts expected by this instruction are those of
+/// [`build_add_to_denylist_instruction`].
+#[cfg(not(target_os = "solana"))]
+pub fn build_remove_from_denylist_instruction(
+    accounts: &DenylistAccounts,
+    proof: Option<CompressedProof>,
+    address: Pubkey,
+    address_tree: Pubkey,
+    merkle_context: PackedMerkleContext,
+    root_index: u16,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: denylist_account_metas(accounts),
+        data: borsh::to_vec(&ClaimProgramInstruction::RemoveFromDenylist {
+            proof,
+            address,
+            address_tree,
+            merkle_context,
+            root_index,
+            output_tree_index: accounts.output_tree_index,
+        })
+        .unwrap(),
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+fn denylist_account_metas(accounts: &DenylistAccounts) -> Vec<AccountMeta> {
+    let mut account_metas = vec![
+        AccountMeta::new(accounts.authority, true),
+        AccountMeta::new_readonly(crate::light_system::find_cpi_authority_pda().0, false),
+        AccountMeta::new_readonly(crate::light_system::LIGHT_SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(accounts.registered_program_pda, false),
+        AccountMeta::new_readonly(accounts.noop_program, false),
+        AccountMeta::new_readonly(accounts.account_compression_authority, false),
+        AccountMeta::new_readonly(accounts.account_compression_program, false),
+        AccountMeta::new_readonly(crate::id(), false),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ];
+    account_metas.extend(
+        accounts
+            .tree_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+    account_metas
+}
+
+/// Build an instruction that pauses or resumes claims.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer]` Admin
+///   1. `[writable]` Config PDA
+#[cfg(not(target_os = "solana"))]
+pub fn build_set_paused_instruction(admin: Pubkey, paused: bool) -> Instruction {
+    build_admin_instruction(admin, ClaimProgramInstruction::SetPaused { paused })
+}
+
+/// Build an instruction that proposes `new_admin` as admin.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer]` Admin
+///   1. `[writable]` Config PDA
+#[cfg(not(target_os = "solana"))]
+pub fn build_transfer_admin_instruction(admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    build_admin_instruction(admin, ClaimProgramInstruction::TransferAdmin { new_admin })
+}
+
+/// Build an instruction that accepts a pending admin transfer.
+///
+/// Accounts expected by this instruction:
+///
+///   0. `[signer]` Pending admin
+///   1. `[writable]` Config PDA
+#[cfg(not(target_os = "solana"))]
+pub fn build_accept_admin_instruction(pending_admin: Pubkey) -> Instruction {
+    build_admin_instruction(pending_admin, ClaimProgramInstruction::AcceptAdmin)
+}
+
+#[cfg(not(target_os = "solana"))]
+fn build_admin_instruction(
+    signer: Pubkey,
+    instruction_data: ClaimProgramInstruction,
+) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(signer, true),
+            AccountMeta::new(crate::pda::find_config_pda().0, false),
+        ],
         data: borsh::to_vec(&instruction_data).unwrap(),
     }
 }
@@ -114,6 +2126,7 @@ pub fn build_claim_and_decompress_instruction(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::Denylist;
 
     #[test]
     fn test_build_claim() {
@@ -132,8 +2145,10 @@ mod tests {
             decompress_destination: Pubkey::new_unique(),
             token_program: Pubkey::new_unique(),
             system_program: Pubkey::new_unique(),
-            state_tree: Pubkey::new_unique(),
-            queue: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            fee_recipient: None,
+            tree_accounts: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            output_tree_index: 0,
         };
 
         let mint = Pubkey::new_unique();
@@ -154,9 +2169,15 @@ mod tests {
             mint,
             unlock_slot,
             bump_seed,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
         );
 
-        assert_eq!(instruction.accounts.len(), 16);
+        assert_eq!(instruction.accounts.len(), 18);
         assert_eq!(instruction.accounts[0].pubkey, accounts.claimant);
         assert!(instruction.accounts[0].is_signer);
         assert_eq!(instruction.accounts[1].pubkey, accounts.fee_payer);
@@ -185,6 +2206,492 @@ mod tests {
                 assert_eq!(unlock_slot, _unlock_slot);
                 assert_eq!(bump_seed, _bump_seed);
             }
+            _ => panic!("Expected a Claim instruction."),
+        }
+    }
+
+    const KEY: [u8; 32] = [7; 32];
+    /// proof None, root_index 4, merkle_context(), amount 5, lamports None
+    const INPUT: [u8; 19] = [0, 4, 0, 1, 2, 3, 0, 0, 0, 1, 5, 0, 0, 0, 0, 0, 0, 0, 0];
+
+    fn merkle_context() -> PackedMerkleContext {
+        PackedMerkleContext {
+            merkle_tree_pubkey_index: 1,
+            queue_pubkey_index: 2,
+            leaf_index: 3,
+            prove_by_index: true,
+        }
+    }
+
+    fn fee() -> ClaimFee {
+        ClaimFee {
+            amount: crate::state::FeeAmount::Bps(100),
+            recipient: Pubkey::new_from_array(KEY),
+        }
+    }
+
+    fn grant() -> Grant {
+        Grant {
+            grantor: Pubkey::new_from_array(KEY),
+            employee: Pubkey::new_from_array(KEY),
+            mint: Pubkey::new_from_array(KEY),
+            amount: 8,
+            start_slot: 9,
+            end_slot: 10,
+        }
+    }
+
+    fn assert_golden(instruction: ClaimProgramInstruction, expected: &[&[u8]]) {
+        let expected = expected.concat();
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        let decoded = ClaimProgramInstruction::try_from_slice(&expected).unwrap();
+        assert_eq!(decoded.discriminator(), instruction.discriminator());
+        assert_eq!(borsh::to_vec(&decoded).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_golden_bytes() {
+        let key = Pubkey::new_from_array(KEY);
+        let fee_bytes: &[u8] = &[&[1u8, 1, 100, 0][..], &KEY].concat();
+        let grant_bytes: &[u8] = &[
+            &KEY[..],
+            &KEY,
+            &KEY,
+            &8u64.to_le_bytes(),
+            &9u64.to_le_bytes(),
+            &10u64.to_le_bytes(),
+        ]
+        .concat();
+        // mint KEY, unlock_slot 6, bump_seed 255
+        let claim_bytes: &[u8] = &[&INPUT[..], &KEY, &6u64.to_le_bytes(), &[255]].concat();
+
+        assert_golden(
+            ClaimProgramInstruction::Claim {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                fee: Some(fee()),
+                claim_amount: Some(3),
+                output_tree_index: 1,
+                attestation: Some(Attestation {
+                    attestor: key,
+                    expiry_slot: 7,
+                }),
+                denylist: Some(DenylistCheck {
+                    denylist: Denylist {
+                        authority: key,
+                        address_tree: key,
+                    },
+                    entry: DenylistEntryProof::Absent {
+                        address_tree_index: 3,
+                        address_root_index: 11,
+                    },
+                    cpi_context_index: 2,
+                }),
+                expiry_slot: Some(8),
+                decay: Some(ClaimDecay {
+                    treasury: key,
+                    decay_start_slot: 9,
+                    end_slot: 10,
+                }),
+            },
+            &[
+                &[1, 0],
+                claim_bytes,
+                fee_bytes,
+                &[1],
+                &3u64.to_le_bytes(),
+                &[1],
+                &[1],
+                &KEY,
+                &7u64.to_le_bytes(),
+                &[1],
+                &KEY,
+                &KEY,
+                &[0, 3],
+                &11u16.to_le_bytes(),
+                &[2],
+                &[1],
+                &8u64.to_le_bytes(),
+                &[1],
+                &KEY,
+                &9u64.to_le_bytes(),
+                &10u64.to_le_bytes(),
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::InitializeConfig { admin: key },
+            &[&[1, 1], &KEY],
+        );
+        assert_golden(
+            ClaimProgramInstruction::SetPaused { paused: true },
+            &[&[1, 2, 1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::TransferAdmin { new_admin: key },
+            &[&[1, 3], &KEY],
+        );
+        assert_golden(ClaimProgramInstruction::AcceptAdmin, &[&[1, 4]]);
+        assert_golden(
+            ClaimProgramInstruction::CreateGrant {
+                grant: grant(),
+                bump_seed: 255,
+                output_tree_index: 1,
+            },
+            &[&[1, 5], grant_bytes, &[255], &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::ClaimGrant {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                grant: grant(),
+                bump_seed: 255,
+                output_tree_index: 1,
+            },
+            &[&[1, 6], &INPUT, grant_bytes, &[255], &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::RevokeGrant {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                grant: grant(),
+                bump_seed: 255,
+                revoke_slot: 11,
+                output_tree_index: 1,
+            },
+            &[
+                &[1, 7],
+                &INPUT,
+                grant_bytes,
+                &[255],
+                &11u64.to_le_bytes(),
+                &[1],
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::TransferBeneficiary {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                terms: AirdropTerms {
+                    expiry_slot: Some(7),
+                    ..AirdropTerms::default()
+                },
+                new_claimant: key,
+                output_tree_index: 1,
+            },
+            &[
+                &[1, 8],
+                claim_bytes,
+                &[0, 0, 0, 0, 1],
+                &7u64.to_le_bytes(),
+                &[0, 0],
+                &KEY,
+                &[1],
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::ClaimMultisig {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                fee: None,
+                multisig: ClaimMultisig {
+                    threshold: 1,
+                    signers: vec![key],
+                },
+                output_tree_index: 1,
+            },
+            &[&[1, 9], claim_bytes, &[0], &[1, 1, 0, 0, 0], &KEY, &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::ClaimWithHook {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                fee: None,
+                hook: key,
+                output_tree_index: 1,
+            },
+            &[&[1, 10], claim_bytes, &[0], &KEY, &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::GetClaimable {
+                source: ClaimSource::Grant(grant()),
+                amount: 5,
+                bump_seed: 255,
+            },
+            &[&[1, 11, 1], grant_bytes, &5u64.to_le_bytes(), &[255]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::AddToDenylist {
+                proof: None,
+                address: key,
+                address_tree: key,
+                entry: DenylistEntryProof::Existing {
+                    merkle_context: merkle_context(),
+                    root_index: 4,
+                },
+                output_tree_index: 1,
+            },
+            &[&[1, 12, 0], &KEY, &KEY, &[1], &INPUT[3..10], &[4, 0], &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::RemoveFromDenylist {
+                proof: None,
+                address: key,
+                address_tree: key,
+                merkle_context: merkle_context(),
+                root_index: 4,
+                output_tree_index: 1,
+            },
+            &[&[1, 13, 0], &KEY, &KEY, &INPUT[3..10], &[4, 0], &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::ClaimBasket {
+                proof: None,
+                unlock_slot: 6,
+                claims: vec![BasketClaim {
+                    root_index: 4,
+                    merkle_context: merkle_context(),
+                    amount: 5,
+                    lamports: None,
+                    mint: key,
+                    bump_seed: 255,
+                    terms: AirdropTerms {
+                        fee: Some(fee()),
+                        expiry_slot: Some(7),
+                        ..AirdropTerms::default()
+                    },
+                }],
+                cpi_context_index: 3,
+                output_tree_index: 1,
+            },
+            &[
+                &[1, 14],
+                &[0],
+                &6u64.to_le_bytes(),
+                &1u32.to_le_bytes(),
+                &INPUT[1..],
+                &KEY,
+                &[255],
+                fee_bytes,
+                &[0, 0, 0, 1],
+                &7u64.to_le_bytes(),
+                &[0, 0],
+                &[3, 1],
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::ClaimLamports {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                lamports: 5,
+                unlock_slot: 6,
+                bump_seed: 255,
+            },
+            &[&[1, 15], &INPUT[..18], &6u64.to_le_bytes(), &[255]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::EarlyClaim {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                fee: None,
+                early_unlock: EarlyUnlock {
+                    treasury: key,
+                    penalty_bps: 100,
+                    start_slot: 7,
+                },
+                output_tree_index: 1,
+            },
+            &[
+                &[1, 16],
+                claim_bytes,
+                &[0],
+                &KEY,
+                &100u16.to_le_bytes(),
+                &7u64.to_le_bytes(),
+                &[1],
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::Relock {
+                proof: None,
+                root_index: 4,
+                merkle_context: merkle_context(),
+                amount: 5,
+                lamports: None,
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                fee: None,
+                new_unlock_slot: 7,
+                bonus: Some(RelockBonusInput {
+                    proof: None,
+                    root_index: 4,
+                    merkle_context: merkle_context(),
+                    amount: 5,
+                    lamports: None,
+                    bonus: RelockBonus {
+                        funder: key,
+                        rate_bps: 100,
+                        period_slots: 8,
+                    },
+                    bump_seed: 254,
+                }),
+                output_tree_index: 1,
+            },
+            &[
+                &[1, 17],
+                claim_bytes,
+                &[0],
+                &7u64.to_le_bytes(),
+                &[1],
+                &INPUT,
+                &KEY,
+                &100u16.to_le_bytes(),
+                &8u64.to_le_bytes(),
+                &[254, 1],
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::Merge {
+                mint: key,
+                unlock_slot: 6,
+                bump_seed: 255,
+                fee: None,
+                positions: vec![MergePosition {
+                    proof: None,
+                    root_index: 4,
+                    merkle_context: merkle_context(),
+                    amount: 5,
+                    lamports: None,
+                    unlock_slot: 2,
+                    bump_seed: 254,
+                }],
+                output_tree_index: 1,
+            },
+            &[
+                &[1, 18],
+                &KEY,
+                &6u64.to_le_bytes(),
+                &[255, 0],
+                &1u32.to_le_bytes(),
+                &INPUT,
+                &2u64.to_le_bytes(),
+                &[254, 1],
+            ],
+        );
+        assert_golden(
+            ClaimProgramInstruction::CreateMigration {
+                old_mint: key,
+                new_mint: key,
+                ratio_numerator: 3,
+                ratio_denominator: 2,
+                bump_seed: 255,
+            },
+            &[
+                &[1, 19],
+                &KEY,
+                &KEY,
+                &3u64.to_le_bytes(),
+                &2u64.to_le_bytes(),
+                &[255],
+            ],
+        );
+        let migration_input = || MigrationInput {
+            proof: None,
+            root_index: 4,
+            merkle_context: merkle_context(),
+            amount: 5,
+            lamports: None,
+        };
+        assert_golden(
+            ClaimProgramInstruction::Migrate {
+                amount: 7,
+                source: MigrationSource::Compressed(migration_input()),
+                vault: migration_input(),
+                output_tree_index: 1,
+            },
+            &[&[1, 20], &7u64.to_le_bytes(), &[1], &INPUT, &INPUT, &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::Migrate {
+                amount: 7,
+                source: MigrationSource::Spl,
+                vault: migration_input(),
+                output_tree_index: 1,
+            },
+            &[&[1, 20], &7u64.to_le_bytes(), &[0], &INPUT, &[1]],
+        );
+        assert_golden(
+            ClaimProgramInstruction::WithdrawMigration {
+                mint: key,
+                input: migration_input(),
+                output_tree_index: 1,
+            },
+            &[&[1, 21], &KEY, &INPUT, &[1]],
+        );
+    }
+
+    #[test]
+    fn test_instruction_version() {
+        let claim_bytes = [&INPUT[..], &KEY, &6u64.to_le_bytes(), &[255]].concat();
+
+        // unversioned legacy claims are rejected.
+        let legacy = [&[0u8][..], &claim_bytes].concat();
+        assert!(ClaimProgramInstruction::try_from_slice(&legacy).is_err());
+
+        // claims without attestation, denylist, expiry slot or decay may omit
+        // them.
+        let claim = [&[1u8, 0][..], &claim_bytes, &[0, 0, 1]].concat();
+        match ClaimProgramInstruction::try_from_slice(&claim).unwrap() {
+            ClaimProgramInstruction::Claim {
+                output_tree_index: 1,
+                attestation: None,
+                denylist: None,
+                expiry_slot: None,
+                decay: None,
+                ..
+            } => {}
+            _ => panic!("Expected a Claim instruction without attestation."),
         }
+
+        // unknown versions and discriminators are rejected.
+        assert!(ClaimProgramInstruction::try_from_slice(&[2, 0]).is_err());
+        assert!(ClaimProgramInstruction::try_from_slice(&[1, 255]).is_err());
     }
 }
@@ -0,0 +1,102 @@
+//! Packing of state trees and queues into the tree accounts of a claim.
+use light_compressed_account::compressed_account::PackedMerkleContext;
+use solana_program::pubkey::Pubkey;
+
+/// Builds the deduplicated tree accounts of a claim instruction, and the
+/// packed merkle contexts and output tree indices that reference them.
+///
+/// Every packed account is referenced, so the program reads exactly the
+/// packed tree accounts.
+#[derive(Debug, Default, Clone)]
+pub struct PackedClaimAccounts {
+    tree_accounts: Vec<Pubkey>,
+}
+
+impl PackedClaimAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index of `pubkey` in the tree accounts, appended if not packed yet.
+    pub fn insert_or_get(&mut self, pubkey: Pubkey) -> u8 {
+        let index = match self.tree_accounts.iter().position(|key| *key == pubkey) {
+            Some(index) => index,
+            None => {
+                self.tree_accounts.push(pubkey);
+                self.tree_accounts.len() - 1
+            }
+        };
+        u8::try_from(index).expect("More than 256 tree accounts.")
+    }
+
+    /// Packed merkle context of an input compressed account in `tree`.
+    /// `queue` is the nullifier queue of a v1 state tree or the output queue
+    /// of a batched state tree.
+    pub fn pack_merkle_context(
+        &mut self,
+        tree: Pubkey,
+        queue: Pubkey,
+        leaf_index: u32,
+        prove_by_index: bool,
+    ) -> PackedMerkleContext {
+        PackedMerkleContext {
+            merkle_tree_pubkey_index: self.insert_or_get(tree),
+            queue_pubkey_index: self.insert_or_get(queue),
+            leaf_index,
+            prove_by_index,
+        }
+    }
+
+    /// Output tree index of a v1 state tree or a batched output queue.
+    pub fn pack_output_tree(&mut self, tree: Pubkey) -> u8 {
+        self.insert_or_get(tree)
+    }
+
+    /// Tree accounts for `ClaimAccounts::tree_accounts`.
+    pub fn tree_accounts(&self) -> &[Pubkey] {
+        &self.tree_accounts
+    }
+
+    pub fn into_tree_accounts(self) -> Vec<Pubkey> {
+        self.tree_accounts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_tree_accounts() {
+        let v1_tree = Pubkey::new_unique();
+        let v1_queue = Pubkey::new_unique();
+        let v2_tree = Pubkey::new_unique();
+        let v2_queue = Pubkey::new_unique();
+
+        let mut packed = PackedClaimAccounts::new();
+        let first = packed.pack_merkle_context(v1_tree, v1_queue, 7, false);
+        let second = packed.pack_merkle_context(v2_tree, v2_queue, 8, true);
+        // inputs in the same tree share its accounts.
+        let third = packed.pack_merkle_context(v1_tree, v1_queue, 9, false);
+        let output_tree_index = packed.pack_output_tree(v2_queue);
+
+        assert_eq!(
+            (first.merkle_tree_pubkey_index, first.queue_pubkey_index),
+            (0, 1)
+        );
+        assert_eq!(
+            (second.merkle_tree_pubkey_index, second.queue_pubkey_index),
+            (2, 3)
+        );
+        assert_eq!(
+            (third.merkle_tree_pubkey_index, third.queue_pubkey_index),
+            (0, 1)
+        );
+        assert_eq!((third.leaf_index, third.prove_by_index), (9, false));
+        assert_eq!(output_tree_index, 3);
+        assert_eq!(
+            packed.into_tree_accounts(),
+            vec![v1_tree, v1_queue, v2_tree, v2_queue]
+        );
+    }
+}
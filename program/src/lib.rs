@@ -1,22 +1,20 @@
-//! Program entrypoint
-#![cfg(not(feature = "no-entrypoint"))]
-use solana_program::{
-    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
-};
-
-entrypoint!(process_instruction);
-
-pub fn process_instruction(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    instruction_data: &[u8],
-) -> ProgramResult {
-    crate::processor::process_instruction(program_id, accounts, instruction_data)
-}
-
+pub mod attestation;
+#[cfg(feature = "cpi")]
+pub mod cpi;
+mod ctoken;
+#[cfg(not(target_os = "solana"))]
+pub mod discovery;
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint;
 mod error;
+pub mod hook;
 pub mod instruction;
+mod light_system;
+#[cfg(not(target_os = "solana"))]
+pub mod lookup_table;
+pub mod pda;
 pub mod processor;
+pub mod state;
 pub use solana_program;
 
 solana_program::declare_id!("7UHB3CfWv7SugNhfdyP7aeZJPMjnpd9zJ7xYkHozB3Na");
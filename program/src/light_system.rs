@@ -0,0 +1,347 @@
+//! CPIs into the Light system program for compressed SOL and the compressed
+//! accounts of the claim program.
+use light_compressed_account::{
+    compressed_account::{CompressedAccount, PackedCompressedAccountWithMerkleContext},
+    instruction_data::{
+        compressed_proof::CompressedProof,
+        cpi_context::CompressedCpiContext,
+        data::{
+            InstructionDataInvoke, NewAddressParamsAssignedPacked,
+            OutputCompressedAccountWithPackedContext, PackedReadOnlyAddress,
+        },
+        with_readonly::{InAccount, InstructionDataInvokeCpiWithReadOnly},
+    },
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const LIGHT_SYSTEM_PROGRAM_ID: Pubkey = pubkey!("SySTEM1eSU2p4BGQfQpimFEWWSC1XDFeun3Nqzz3rT7");
+
+/// Anchor discriminator of the Light system `invoke` instruction.
+const INVOKE_DISCRIMINATOR: [u8; 8] = [26, 16, 169, 7, 21, 202, 242, 25];
+
+/// Anchor discriminator of the Light system `invoke_cpi_with_read_only`
+/// instruction.
+const INVOKE_CPI_WITH_READ_ONLY_DISCRIMINATOR: [u8; 8] = [86, 47, 163, 166, 21, 223, 92, 8];
+
+const SOL_POOL_SEED: &[u8] = b"sol_pool_pda";
+
+/// Seed of the claim program PDA that signs its Light system CPIs.
+pub const CPI_AUTHORITY_SEED: &[u8] = b"cpi_authority";
+
+/// Derive the CPI authority PDA of the claim program.
+///
+/// Returns (cpi_authority_pda, bump_seed)
+pub fn find_cpi_authority_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CPI_AUTHORITY_SEED], &crate::id())
+}
+
+/// Derive the Light system program PDA that holds compressed lamports.
+#[cfg(not(target_os = "solana"))]
+pub fn find_sol_pool_pda() -> Pubkey {
+    Pubkey::find_program_address(&[SOL_POOL_SEED], &LIGHT_SYSTEM_PROGRAM_ID).0
+}
+
+/// Accounts of a Light system `invoke` CPI.
+pub struct LightSystemCpiAccounts<'a, 'info> {
+    pub fee_payer: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub light_system_program: AccountInfo<'info>,
+    pub registered_program_pda: AccountInfo<'info>,
+    pub noop_program: AccountInfo<'info>,
+    pub account_compression_authority: AccountInfo<'info>,
+    pub account_compression_program: AccountInfo<'info>,
+    pub sol_pool_pda: AccountInfo<'info>,
+    pub decompress_destination: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// Packed state trees and queues, indexed by merkle contexts.
+    pub tree_accounts: &'a [AccountInfo<'info>],
+}
+
+/// Accounts of a Light system CPI that reads and writes compressed accounts
+/// of the claim program.
+pub struct InvokeCpiAccounts<'a, 'info> {
+    pub fee_payer: AccountInfo<'info>,
+    /// CPI authority PDA of the claim program.
+    pub cpi_authority_pda: AccountInfo<'info>,
+    pub light_system_program: AccountInfo<'info>,
+    pub registered_program_pda: AccountInfo<'info>,
+    pub noop_program: AccountInfo<'info>,
+    pub account_compression_authority: AccountInfo<'info>,
+    pub account_compression_program: AccountInfo<'info>,
+    /// The claim program.
+    pub invoking_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    /// Packed address trees, state trees and queues.
+    pub tree_accounts: &'a [AccountInfo<'info>],
+    /// Index of the CPI context account in `tree_accounts`, its stored
+    /// instructions are executed with this one under its proof.
+    pub cpi_context_index: Option<u8>,
+}
+
+/// Creates the `new_address_params` addresses, spends the `inputs` and
+/// creates the `outputs` of the claim program, and proves that the
+/// `read_only_addresses` don't exist.
+pub fn invoke_cpi(
+    proof: Option<CompressedProof>,
+    new_address_params: Vec<NewAddressParamsAssignedPacked>,
+    inputs: Vec<InAccount>,
+    outputs: Vec<OutputCompressedAccountWithPackedContext>,
+    read_only_addresses: Vec<PackedReadOnlyAddress>,
+    light_cpi_accounts: &InvokeCpiAccounts,
+) -> ProgramResult {
+    // CHECK:
+    if *light_cpi_accounts.light_system_program.key != LIGHT_SYSTEM_PROGRAM_ID {
+        msg!("Invalid light system program.");
+        light_cpi_accounts.light_system_program.key.log();
+        return Err(ProgramError::InvalidArgument);
+    }
+    // CHECK:
+    let (cpi_authority_pda, bump_seed) = find_cpi_authority_pda();
+    if *light_cpi_accounts.cpi_authority_pda.key != cpi_authority_pda {
+        msg!(
+            "Invalid CPI authority PDA provided. Expected: {}. Found: {}.",
+            cpi_authority_pda,
+            light_cpi_accounts.cpi_authority_pda.key
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    let cpi_context_account = match light_cpi_accounts.cpi_context_index {
+        Some(index) => Some(
+            light_cpi_accounts
+                .tree_accounts
+                .get(index as usize)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?,
+        ),
+        None => None,
+    };
+
+    let inputs = InstructionDataInvokeCpiWithReadOnly {
+        mode: 0,
+        bump: bump_seed,
+        invoking_program_id: crate::id().to_bytes().into(),
+        compress_or_decompress_lamports: 0,
+        is_compress: false,
+        with_cpi_context: cpi_context_account.is_some(),
+        with_transaction_hash: false,
+        cpi_context: CompressedCpiContext {
+            set_context: false,
+            first_set_context: false,
+            cpi_context_account_index: light_cpi_accounts.cpi_context_index.unwrap_or_default(),
+        },
+        proof,
+        new_address_params,
+        input_compressed_accounts: inputs,
+        output_compressed_accounts: outputs,
+        read_only_addresses,
+        read_only_accounts: Vec::new(),
+    };
+    let mut data = INVOKE_CPI_WITH_READ_ONLY_DISCRIMINATOR.to_vec();
+    borsh::BorshSerialize::serialize(&borsh::to_vec(&inputs)?, &mut data)?;
+
+    // Optional accounts the Light system program doesn't need are set to its
+    // own program id.
+    let mut accounts = vec![
+        AccountMeta::new(*light_cpi_accounts.fee_payer.key, true),
+        AccountMeta::new_readonly(cpi_authority_pda, true),
+        AccountMeta::new_readonly(*light_cpi_accounts.registered_program_pda.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.noop_program.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.account_compression_authority.key, false),
+        AccountMeta::new_readonly(*light_cpi_accounts.account_compression_program.key, false),
+        AccountMeta::new_readonly(crate::id(), false),
+        AccountMeta::new_readonly(LIGHT_SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(LIGHT_SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+        match cpi_context_account {
+            Some(cpi_context_account) => AccountMeta::new(*cpi_context_account.key, false),
+            None => AccountMeta::new_readonly(LIGHT_SYSTEM_PROGRAM_ID, false),
+        },
+    ];
+    accounts.extend(
+        light_cpi_accounts
+            .tree_accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account.key, false)),
+    );
+    let instruction = Instruction {
+        program_id: LIGHT_SYSTEM_PROGRAM_ID,
+        accounts,
+        data,
+    };
+
+    let mut account_infos = vec![
+        light_cpi_accounts.fee_payer.clone(),
+        light_cpi_accounts.cpi_authority_pda.clone(),
+        light_cpi_accounts.registered_program_pda.clone(),
+        light_cpi_accounts.noop_program.clone(),
+        light_cpi_accounts.account_compression_authority.clone(),
+        light_cpi_accounts.account_compression_program.clone(),
+        light_cpi_accounts.invoking_program.clone(),
+        light_cpi_accounts.system_program.clone(),
+        light_cpi_accounts.light_system_program.clone(),
+    ];
+    account_infos.extend_from_slice(light_cpi_accounts.tree_accounts);
+    invoke_signed(
+        &instruction,
+        &account_infos,
+        &[&[CPI_AUTHORITY_SEED, &[bump_seed]]],
+    )
+}
+
+/// Spends the compressed SOL account of the authority holding `lamports` and
+/// decompresses all of it to the decompress destination.
+pub fn decompress_lamports(
+    input: PackedCompressedAccountWithMerkleContext,
+    proof: Option<CompressedProof>,
+    light_cpi_accounts: &LightSystemCpiAccounts,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let lamports = input.compressed_account.lamports;
+    let inputs = InstructionDataInvoke {
+        proof,
+        input_compressed_accounts_with_merkle_context: vec![input],
+        output_compressed_accounts: Vec::new(),
+        relay_fee: None,
+        new_address_params: Vec::new(),
+        compress_or_decompress_lamports: Some(lamports),
+        is_compress: false,
+    };
+    let instruction = invoke_instruction(
+        &inputs,
+        invoke_account_metas(
+            *light_cpi_accounts.fee_payer.key,
+            *light_cpi_accounts.authority.key,
+            *light_cpi_accounts.registered_program_pda.key,
+            *light_cpi_accounts.noop_program.key,
+            *light_cpi_accounts.account_compression_authority.key,
+            *light_cpi_accounts.account_compression_program.key,
+            *light_cpi_accounts.sol_pool_pda.key,
+            Some(*light_cpi_accounts.decompress_destination.key),
+            light_cpi_accounts
+                .tree_accounts
+                .iter()
+                .map(|account| *account.key),
+        ),
+    );
+
+    let mut account_infos = vec![
+        light_cpi_accounts.fee_payer.clone(),
+        light_cpi_accounts.authority.clone(),
+        light_cpi_accounts.registered_program_pda.clone(),
+        light_cpi_accounts.noop_program.clone(),
+        light_cpi_accounts.account_compression_authority.clone(),
+        light_cpi_accounts.account_compression_program.clone(),
+        light_cpi_accounts.sol_pool_pda.clone(),
+        light_cpi_accounts.decompress_destination.clone(),
+        light_cpi_accounts.system_program.clone(),
+        light_cpi_accounts.light_system_program.clone(),
+    ];
+    account_infos.extend_from_slice(light_cpi_accounts.tree_accounts);
+    invoke_signed(&instruction, &account_infos, signers_seeds)
+}
+
+/// Build a Light system instruction that compresses `lamports` of `payer` to
+/// a compressed SOL account of `owner` in `output_tree`, a v1 state tree.
+#[cfg(not(target_os = "solana"))]
+#[allow(clippy::too_many_arguments)]
+pub fn build_compress_lamports_instruction(
+    payer: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    registered_program_pda: Pubkey,
+    noop_program: Pubkey,
+    account_compression_authority: Pubkey,
+    account_compression_program: Pubkey,
+    output_tree: Pubkey,
+) -> Instruction {
+    let inputs = InstructionDataInvoke {
+        proof: None,
+        input_compressed_accounts_with_merkle_context: Vec::new(),
+        output_compressed_accounts: vec![OutputCompressedAccountWithPackedContext {
+            compressed_account: compressed_sol_account(&owner, lamports),
+            merkle_tree_index: 0,
+        }],
+        relay_fee: None,
+        new_address_params: Vec::new(),
+        compress_or_decompress_lamports: Some(lamports),
+        is_compress: true,
+    };
+    invoke_instruction(
+        &inputs,
+        invoke_account_metas(
+            payer,
+            payer,
+            registered_program_pda,
+            noop_program,
+            account_compression_authority,
+            account_compression_program,
+            find_sol_pool_pda(),
+            None,
+            [output_tree],
+        ),
+    )
+}
+
+/// Returns the compressed SOL account of `owner` holding `lamports`.
+pub fn compressed_sol_account(owner: &Pubkey, lamports: u64) -> CompressedAccount {
+    CompressedAccount {
+        owner: owner.to_bytes().into(),
+        lamports,
+        address: None,
+        data: None,
+    }
+}
+
+fn invoke_instruction(inputs: &InstructionDataInvoke, accounts: Vec<AccountMeta>) -> Instruction {
+    let mut data = INVOKE_DISCRIMINATOR.to_vec();
+    borsh::BorshSerialize::serialize(&borsh::to_vec(inputs).unwrap(), &mut data).unwrap();
+    Instruction {
+        program_id: LIGHT_SYSTEM_PROGRAM_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Accounts of the Light system `invoke` instruction. Optional accounts the
+/// Light system program doesn't need are set to its own program id.
+#[allow(clippy::too_many_arguments)]
+fn invoke_account_metas(
+    fee_payer: Pubkey,
+    authority: Pubkey,
+    registered_program_pda: Pubkey,
+    noop_program: Pubkey,
+    account_compression_authority: Pubkey,
+    account_compression_program: Pubkey,
+    sol_pool_pda: Pubkey,
+    decompress_destination: Option<Pubkey>,
+    tree_accounts: impl IntoIterator<Item = Pubkey>,
+) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta::new(fee_payer, true),
+        AccountMeta::new_readonly(authority, true),
+        AccountMeta::new_readonly(registered_program_pda, false),
+        AccountMeta::new_readonly(noop_program, false),
+        AccountMeta::new_readonly(account_compression_authority, false),
+        AccountMeta::new_readonly(account_compression_program, false),
+        AccountMeta::new(sol_pool_pda, false),
+        match decompress_destination {
+            Some(decompress_destination) => AccountMeta::new(decompress_destination, false),
+            None => AccountMeta::new_readonly(LIGHT_SYSTEM_PROGRAM_ID, false),
+        },
+        AccountMeta::new_readonly(solana_program::system_program::ID, false),
+    ];
+    accounts.extend(
+        tree_accounts
+            .into_iter()
+            .map(|account| AccountMeta::new(account, false)),
+    );
+    accounts
+}
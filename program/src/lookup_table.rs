@@ -0,0 +1,153 @@
+//! Address lookup table for claim transactions.
+//!
+//! A lookup table holding the static accounts of claim instructions lets
+//! claims be sent as v0 transactions that reference those accounts by a one
+//! byte index, leaving room for other instructions.
+use crate::instruction::ClaimAccounts;
+use solana_address_lookup_table_interface::instruction::{
+    create_lookup_table, extend_lookup_table,
+};
+use solana_program::{
+    clock::Slot,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, CompileError, VersionedMessage},
+    pubkey::Pubkey,
+};
+
+/// Accounts of `accounts` that are shared by the claims of a campaign: the
+/// Light infrastructure accounts, the config PDA, the token pool and the tree
+/// accounts.
+pub fn claim_lookup_table_addresses(accounts: &ClaimAccounts) -> Vec<Pubkey> {
+    let mut addresses = vec![
+        accounts.ctoken_cpi_authority_pda,
+        accounts.light_system_program,
+        accounts.registered_program_pda,
+        accounts.noop_program,
+        accounts.account_compression_authority,
+        accounts.account_compression_program,
+        accounts.ctoken_program,
+        accounts.token_pool_pda,
+        accounts.token_program,
+        accounts.system_program,
+        accounts.config,
+    ];
+    addresses.extend(&accounts.tree_accounts);
+    addresses
+}
+
+/// Instructions that create a lookup table owned by `authority` and extend
+/// it with the shared accounts of `accounts`. The table can be used from the
+/// slot after it is extended.
+///
+/// Returns (instructions, lookup_table)
+pub fn build_create_claim_lookup_table_instructions(
+    authority: Pubkey,
+    payer: Pubkey,
+    recent_slot: Slot,
+    accounts: &ClaimAccounts,
+) -> (Vec<Instruction>, Pubkey) {
+    let (create_instruction, lookup_table) = create_lookup_table(authority, payer, recent_slot);
+    let extend_instruction = extend_lookup_table(
+        lookup_table,
+        authority,
+        Some(payer),
+        claim_lookup_table_addresses(accounts),
+    );
+    (vec![create_instruction, extend_instruction], lookup_table)
+}
+
+/// Compiles claim `instructions` into a v0 message that loads the shared
+/// accounts from `lookup_table`.
+pub fn compile_claim_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_table: &AddressLookupTableAccount,
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage, CompileError> {
+    let message = v0::Message::try_compile(
+        payer,
+        instructions,
+        std::slice::from_ref(lookup_table),
+        recent_blockhash,
+    )?;
+    Ok(VersionedMessage::V0(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::build_claim_and_decompress_instruction;
+    use light_compressed_account::{
+        compressed_account::PackedMerkleContext,
+        instruction_data::compressed_proof::CompressedProof,
+    };
+    use solana_program::message::Message;
+
+    #[test]
+    fn test_lookup_table_reduces_claim_size() {
+        let payer = Pubkey::new_unique();
+        let accounts = ClaimAccounts {
+            claimant: Pubkey::new_unique(),
+            fee_payer: payer,
+            associated_airdrop_pda: Pubkey::new_unique(),
+            ctoken_cpi_authority_pda: Pubkey::new_unique(),
+            light_system_program: Pubkey::new_unique(),
+            registered_program_pda: Pubkey::new_unique(),
+            noop_program: Pubkey::new_unique(),
+            account_compression_authority: Pubkey::new_unique(),
+            account_compression_program: Pubkey::new_unique(),
+            ctoken_program: Pubkey::new_unique(),
+            token_pool_pda: Pubkey::new_unique(),
+            decompress_destination: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+            system_program: Pubkey::new_unique(),
+            config: Pubkey::new_unique(),
+            fee_recipient: None,
+            tree_accounts: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            output_tree_index: 0,
+        };
+        let instruction = build_claim_and_decompress_instruction(
+            &accounts,
+            Some(CompressedProof {
+                a: [1; 32],
+                b: [2; 64],
+                c: [3; 32],
+            }),
+            0,
+            PackedMerkleContext {
+                merkle_tree_pubkey_index: 0,
+                queue_pubkey_index: 1,
+                leaf_index: 0,
+                prove_by_index: false,
+            },
+            10,
+            None,
+            Pubkey::new_unique(),
+            0,
+            255,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let (_, lookup_table) =
+            build_create_claim_lookup_table_instructions(payer, payer, 0, &accounts);
+        let lookup_table = AddressLookupTableAccount {
+            key: lookup_table,
+            addresses: claim_lookup_table_addresses(&accounts),
+        };
+        let legacy = Message::new(std::slice::from_ref(&instruction), Some(&payer)).serialize();
+        let v0 = compile_claim_message(&payer, &[instruction], &lookup_table, Hash::default())
+            .unwrap()
+            .serialize();
+
+        // 13 of the 18 accounts are loaded from the lookup table. The v0
+        // message adds a version prefix, the table count, key and index
+        // lengths, and one index per loaded account.
+        assert_eq!(legacy.len() - v0.len(), 13 * 32 - (1 + 1 + 32 + 2 + 13));
+    }
+}
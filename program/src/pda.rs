@@ -0,0 +1,337 @@
+use crate::state::{
+    AirdropTerms, ClaimDecay, ClaimFee, ClaimMultisig, Denylist, EarlyUnlock, Grant, RelockBonus,
+};
+use light_compressed_account::address::derive_address;
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const GRANT_SEED: &[u8] = b"grant";
+pub const DENYLIST_SEED: &[u8] = b"denylist";
+pub const LAMPORTS_SEED: &[u8] = b"lamports";
+pub const BONUS_VAULT_SEED: &[u8] = b"bonus_vault";
+pub const MIGRATION_SEED: &[u8] = b"migration";
+
+/// Seeds of an airdrop PDA.
+///
+/// An airdrop PDA commits to (claimant, mint, unlock_slot) and to the optional
+/// campaign terms, such as a fee or the slot at which the claim window
+/// closes. Optional terms only add a seed when they are set, so airdrops
+/// without them keep their original address. Every optional seed is a hash
+/// tagged with its term, so the value of one term never derives the address
+/// of an airdrop with another term.
+#[derive(Debug, Clone)]
+pub struct AirdropSeeds {
+    claimant: [u8; 32],
+    mint: [u8; 32],
+    unlock_slot: [u8; 8],
+    fee: Option<[u8; 32]>,
+    hook: Option<[u8; 32]>,
+    attestor: Option<[u8; 32]>,
+    denylist: Option<[u8; 32]>,
+    expiry_slot: Option<[u8; 32]>,
+    early_unlock: Option<[u8; 32]>,
+    decay: Option<[u8; 32]>,
+}
+
+impl AirdropSeeds {
+    pub fn new(claimant: &Pubkey, mint: &Pubkey, unlock_slot: u64) -> Self {
+        Self {
+            claimant: claimant.to_bytes(),
+            mint: mint.to_bytes(),
+            unlock_slot: unlock_slot.to_le_bytes(),
+            fee: None,
+            hook: None,
+            attestor: None,
+            denylist: None,
+            expiry_slot: None,
+            early_unlock: None,
+            decay: None,
+        }
+    }
+
+    /// Seeds of an airdrop claimable by an M-of-N multisig. The multisig
+    /// commitment takes the place of the claimant.
+    pub fn new_multisig(multisig: &ClaimMultisig, mint: &Pubkey, unlock_slot: u64) -> Self {
+        Self {
+            claimant: multisig.commitment(),
+            mint: mint.to_bytes(),
+            unlock_slot: unlock_slot.to_le_bytes(),
+            fee: None,
+            hook: None,
+            attestor: None,
+            denylist: None,
+            expiry_slot: None,
+            early_unlock: None,
+            decay: None,
+        }
+    }
+
+    pub fn with_fee(mut self, fee: Option<&ClaimFee>) -> Self {
+        self.fee = fee.map(ClaimFee::commitment);
+        self
+    }
+
+    /// Commits to the program invoked after the claim.
+    pub fn with_hook(mut self, hook: Option<&Pubkey>) -> Self {
+        self.hook = hook.map(|hook| term_seed(b"hook", hook.as_ref()));
+        self
+    }
+
+    /// Commits to the attestor that must attest claims.
+    pub fn with_attestor(mut self, attestor: Option<&Pubkey>) -> Self {
+        self.attestor = attestor.map(|attestor| term_seed(b"attestor", attestor.as_ref()));
+        self
+    }
+
+    /// Commits to the denylist checked on claim.
+    pub fn with_denylist(mut self, denylist: Option<&Denylist>) -> Self {
+        self.denylist = denylist.map(Denylist::commitment);
+        self
+    }
+
+    /// Commits to the slot from which claims are rejected.
+    pub fn with_expiry_slot(mut self, expiry_slot: Option<u64>) -> Self {
+        self.expiry_slot =
+            expiry_slot.map(|expiry_slot| term_seed(b"expiry_slot", &expiry_slot.to_le_bytes()));
+        self
+    }
+
+    /// Commits to the early unlock terms of `EarlyClaim`.
+    pub fn with_early_unlock(mut self, early_unlock: Option<&EarlyUnlock>) -> Self {
+        self.early_unlock = early_unlock.map(EarlyUnlock::commitment);
+        self
+    }
+
+    /// Commits to the decay of the claimable amount.
+    pub fn with_decay(mut self, decay: Option<&ClaimDecay>) -> Self {
+        self.decay = decay.map(ClaimDecay::commitment);
+        self
+    }
+
+    /// Commits to all optional campaign terms.
+    pub fn with_terms(self, terms: &AirdropTerms) -> Self {
+        self.with_fee(terms.fee.as_ref())
+            .with_hook(terms.hook.as_ref())
+            .with_attestor(terms.attestor.as_ref())
+            .with_denylist(terms.denylist.as_ref())
+            .with_expiry_slot(terms.expiry_slot)
+            .with_early_unlock(terms.early_unlock.as_ref())
+            .with_decay(terms.decay.as_ref())
+    }
+
+    /// Seeds without the bump seed.
+    pub fn seeds(&self) -> Vec<&[u8]> {
+        let mut seeds = vec![&self.claimant[..], &self.mint[..], &self.unlock_slot[..]];
+        if let Some(fee) = &self.fee {
+            seeds.push(&fee[..]);
+        }
+        if let Some(hook) = &self.hook {
+            seeds.push(&hook[..]);
+        }
+        if let Some(attestor) = &self.attestor {
+            seeds.push(&attestor[..]);
+        }
+        if let Some(denylist) = &self.denylist {
+            seeds.push(&denylist[..]);
+        }
+        if let Some(expiry_slot) = &self.expiry_slot {
+            seeds.push(&expiry_slot[..]);
+        }
+        if let Some(early_unlock) = &self.early_unlock {
+            seeds.push(&early_unlock[..]);
+        }
+        if let Some(decay) = &self.decay {
+            seeds.push(&decay[..]);
+        }
+        seeds
+    }
+
+    /// Returns (airdrop_pda, bump_seed)
+    pub fn find_program_address(&self) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&self.seeds(), &crate::id())
+    }
+}
+
+/// Airdrop PDA seed of a term that is a plain value. Commitments of structured
+/// terms such as `ClaimFee::commitment` are tagged the same way.
+fn term_seed(term: &[u8], value: &[u8]) -> [u8; 32] {
+    hashv(&[term, value]).to_bytes()
+}
+
+/// Derive the airdrop PDA that owns the compressed tokens of `claimant` for
+/// `mint`, locked until `unlock_slot`.
+///
+/// Returns (airdrop_pda, bump_seed)
+pub fn find_airdrop_pda(claimant: &Pubkey, mint: &Pubkey, unlock_slot: u64) -> (Pubkey, u8) {
+    AirdropSeeds::new(claimant, mint, unlock_slot).find_program_address()
+}
+
+/// Derive the airdrop PDA that owns the compressed tokens of `multisig` for
+/// `mint`, locked until `unlock_slot`.
+///
+/// Returns (airdrop_pda, bump_seed)
+pub fn find_multisig_airdrop_pda(
+    multisig: &ClaimMultisig,
+    mint: &Pubkey,
+    unlock_slot: u64,
+) -> (Pubkey, u8) {
+    AirdropSeeds::new_multisig(multisig, mint, unlock_slot).find_program_address()
+}
+
+/// Derive the airdrop PDA that owns the compressed SOL of `claimant`, locked
+/// until `unlock_slot`. Lamports airdrops have their own seed domain without
+/// a mint.
+///
+/// Returns (airdrop_pda, bump_seed)
+pub fn find_lamports_airdrop_pda(claimant: &Pubkey, unlock_slot: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LAMPORTS_SEED, claimant.as_ref(), &unlock_slot.to_le_bytes()],
+        &crate::id(),
+    )
+}
+
+/// Address seed of the entry of `address` in the denylist of `authority`,
+/// truncated to the bn254 field size.
+pub fn denylist_entry_seed(authority: &Pubkey, address: &Pubkey) -> [u8; 32] {
+    let mut seed = hashv(&[DENYLIST_SEED, authority.as_ref(), address.as_ref()]).to_bytes();
+    seed[0] = 0;
+    seed
+}
+
+/// Derive the compressed account address of the entry of `address` in
+/// `denylist`.
+pub fn derive_denylist_entry_address(denylist: &Denylist, address: &Pubkey) -> [u8; 32] {
+    derive_address(
+        &denylist_entry_seed(&denylist.authority, address),
+        &denylist.address_tree.to_bytes(),
+        &crate::id().to_bytes(),
+    )
+}
+
+/// Derive the bonus vault PDA of `bonus.funder` that pays relock bonuses of
+/// `mint` at the rate of `bonus`.
+///
+/// Returns (bonus_vault_pda, bump_seed)
+pub fn find_bonus_vault_pda(mint: &Pubkey, bonus: &RelockBonus) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            BONUS_VAULT_SEED,
+            bonus.funder.as_ref(),
+            mint.as_ref(),
+            &bonus.commitment(),
+        ],
+        &crate::id(),
+    )
+}
+
+/// Derive the migration PDA of `authority` from `old_mint` to `new_mint`. It
+/// holds the migration state, the new tokens to release and the escrowed old
+/// tokens.
+///
+/// Returns (migration_pda, bump_seed)
+pub fn find_migration_pda(
+    authority: &Pubkey,
+    old_mint: &Pubkey,
+    new_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            MIGRATION_SEED,
+            authority.as_ref(),
+            old_mint.as_ref(),
+            new_mint.as_ref(),
+        ],
+        &crate::id(),
+    )
+}
+
+/// Derive the program config PDA.
+///
+/// Returns (config_pda, bump_seed)
+pub fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &crate::id())
+}
+
+/// Derive the grant PDA that owns the unclaimed tokens of `grant`.
+///
+/// Returns (grant_pda, bump_seed)
+pub fn find_grant_pda(grant: &Grant) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GRANT_SEED, &grant.commitment()], &crate::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::FeeAmount;
+
+    #[test]
+    fn test_terms_have_distinct_seeds() {
+        let claimant = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let airdrop = || AirdropSeeds::new(&claimant, &mint, 100);
+        let fee = ClaimFee {
+            amount: FeeAmount::Bps(100),
+            recipient: key,
+        };
+        let fee_as_key = Pubkey::new_from_array(fee.commitment());
+
+        let airdrops = [
+            airdrop().with_fee(Some(&fee)),
+            airdrop().with_denylist(Some(&Denylist {
+                authority: fee_as_key,
+                address_tree: key,
+            })),
+            airdrop().with_hook(Some(&fee_as_key)),
+            airdrop().with_attestor(Some(&fee_as_key)),
+            airdrop().with_hook(Some(&key)),
+            airdrop().with_attestor(Some(&key)),
+            airdrop().with_denylist(Some(&Denylist {
+                authority: key,
+                address_tree: key,
+            })),
+            airdrop().with_expiry_slot(Some(7)),
+            airdrop(),
+        ];
+        for (i, a) in airdrops.iter().enumerate() {
+            for b in &airdrops[..i] {
+                assert_ne!(a.seeds().concat(), b.seeds().concat());
+            }
+        }
+    }
+
+    #[test]
+    fn test_denylist_entry_address() {
+        let [authority, address, address_tree] = [(); 3].map(|_| Pubkey::new_unique());
+        let denylist = Denylist {
+            authority,
+            address_tree,
+        };
+        let entry_address = derive_denylist_entry_address(&denylist, &address);
+        assert_eq!(denylist_entry_seed(&authority, &address)[0], 0);
+
+        // the entry is specific to the authority, address tree and address.
+        for (denylist, address) in [
+            (
+                Denylist {
+                    authority: address,
+                    ..denylist
+                },
+                address,
+            ),
+            (
+                Denylist {
+                    address_tree: authority,
+                    ..denylist
+                },
+                address,
+            ),
+            (denylist, authority),
+        ] {
+            assert_ne!(
+                derive_denylist_entry_address(&denylist, &address),
+                entry_address
+            );
+        }
+    }
+}
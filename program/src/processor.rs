@@ -1,22 +1,45 @@
-use crate::{error::ClaimError, instruction::ClaimProgramInstruction};
-use borsh::BorshDeserialize;
+mod denylist;
+mod grant;
+mod lamports;
+mod migration;
+mod relock;
+
+use crate::{
+    attestation::{self, Attestation},
+    ctoken::{self, CtokenCpiAccounts, CTOKEN_PROGRAM_ID},
+    error::ClaimError,
+    hook::{self, ClaimHookData},
+    instruction::{BasketClaim, ClaimProgramInstruction},
+    light_system::InvokeCpiAccounts,
+    pda::{AirdropSeeds, CONFIG_SEED, GRANT_SEED},
+    state::{
+        AirdropTerms, ClaimConfig, ClaimDecay, ClaimFee, ClaimMultisig, ClaimSource, DenylistCheck,
+        DenylistEntryProof, EarlyUnlock, FeeAmount,
+    },
+};
+use borsh::{BorshDeserialize, BorshSerialize};
 use light_compressed_account::{
-    compressed_account::PackedMerkleContext, instruction_data::compressed_proof::CompressedProof,
+    compressed_account::PackedMerkleContext,
+    instruction_data::{compressed_proof::CompressedProof, cpi_context::CompressedCpiContext},
 };
 use light_compressed_token_sdk::{
-    cpi::{
-        self, account_info::get_compressed_token_account_info,
-        accounts::CompressedTokenDecompressCpiAccounts,
-    },
-    state::InputTokenDataWithContext,
+    cpi::account_info::get_compressed_token_account_info,
+    state::{InputTokenDataWithContext, PackedTokenTransferOutputData},
 };
 use solana_program::{
-    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
-    program::invoke_signed, program_error::ProgramError, pubkey, pubkey::Pubkey, sysvar::Sysvar,
+    account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
 };
 
-const CTOKEN_PROGRAM_ID: Pubkey = pubkey!("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m");
-
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -34,6 +57,13 @@ pub fn process_instruction(
             mint,
             unlock_slot,
             bump_seed,
+            fee,
+            claim_amount,
+            output_tree_index,
+            attestation,
+            denylist,
+            expiry_slot,
+            decay,
         } => process_claim(
             program_id,
             accounts,
@@ -45,7 +75,482 @@ pub fn process_instruction(
             mint,
             unlock_slot,
             bump_seed,
+            fee,
+            claim_amount,
+            output_tree_index,
+            attestation,
+            denylist,
+            expiry_slot,
+            decay,
+        ),
+        ClaimProgramInstruction::InitializeConfig { admin } => {
+            process_initialize_config(program_id, accounts, admin)
+        }
+        ClaimProgramInstruction::SetPaused { paused } => {
+            process_set_paused(program_id, accounts, paused)
+        }
+        ClaimProgramInstruction::TransferAdmin { new_admin } => {
+            process_transfer_admin(program_id, accounts, new_admin)
+        }
+        ClaimProgramInstruction::AcceptAdmin => process_accept_admin(program_id, accounts),
+        ClaimProgramInstruction::CreateGrant {
+            grant,
+            bump_seed,
+            output_tree_index,
+        } => grant::process_create_grant(program_id, accounts, grant, bump_seed, output_tree_index),
+        ClaimProgramInstruction::ClaimGrant {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            grant,
+            bump_seed,
+            output_tree_index,
+        } => grant::process_claim_grant(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            grant,
+            bump_seed,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::RevokeGrant {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            grant,
+            bump_seed,
+            revoke_slot,
+            output_tree_index,
+        } => grant::process_revoke_grant(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            grant,
+            bump_seed,
+            revoke_slot,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::TransferBeneficiary {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            terms,
+            new_claimant,
+            output_tree_index,
+        } => process_transfer_beneficiary(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            terms,
+            new_claimant,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::ClaimMultisig {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            multisig,
+            output_tree_index,
+        } => process_claim_multisig(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            multisig,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::ClaimWithHook {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            hook,
+            output_tree_index,
+        } => process_claim_with_hook(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            hook,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::GetClaimable {
+            source,
+            amount,
+            bump_seed,
+        } => process_get_claimable(program_id, accounts, source, amount, bump_seed),
+        ClaimProgramInstruction::AddToDenylist {
+            proof,
+            address,
+            address_tree,
+            entry,
+            output_tree_index,
+        } => denylist::process_add_to_denylist(
+            accounts,
+            proof,
+            address,
+            address_tree,
+            entry,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::RemoveFromDenylist {
+            proof,
+            address,
+            address_tree,
+            merkle_context,
+            root_index,
+            output_tree_index,
+        } => denylist::process_remove_from_denylist(
+            accounts,
+            proof,
+            address,
+            address_tree,
+            merkle_context,
+            root_index,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::ClaimBasket {
+            proof,
+            unlock_slot,
+            claims,
+            cpi_context_index,
+            output_tree_index,
+        } => process_claim_basket(
+            program_id,
+            accounts,
+            proof,
+            unlock_slot,
+            claims,
+            cpi_context_index,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::ClaimLamports {
+            proof,
+            root_index,
+            merkle_context,
+            lamports,
+            unlock_slot,
+            bump_seed,
+        } => lamports::process_claim_lamports(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            lamports,
+            unlock_slot,
+            bump_seed,
+        ),
+        ClaimProgramInstruction::EarlyClaim {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            early_unlock,
+            output_tree_index,
+        } => process_early_claim(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            early_unlock,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::Relock {
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            new_unlock_slot,
+            bonus,
+            output_tree_index,
+        } => relock::process_relock(
+            program_id,
+            accounts,
+            proof,
+            root_index,
+            merkle_context,
+            amount,
+            lamports,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            new_unlock_slot,
+            bonus,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::Merge {
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            positions,
+            output_tree_index,
+        } => relock::process_merge(
+            program_id,
+            accounts,
+            mint,
+            unlock_slot,
+            bump_seed,
+            fee,
+            positions,
+            output_tree_index,
         ),
+        ClaimProgramInstruction::CreateMigration {
+            old_mint,
+            new_mint,
+            ratio_numerator,
+            ratio_denominator,
+            bump_seed,
+        } => migration::process_create_migration(
+            program_id,
+            accounts,
+            old_mint,
+            new_mint,
+            ratio_numerator,
+            ratio_denominator,
+            bump_seed,
+        ),
+        ClaimProgramInstruction::Migrate {
+            amount,
+            source,
+            vault,
+            output_tree_index,
+        } => migration::process_migrate(
+            program_id,
+            accounts,
+            amount,
+            source,
+            vault,
+            output_tree_index,
+        ),
+        ClaimProgramInstruction::WithdrawMigration {
+            mint,
+            input,
+            output_tree_index,
+        } => migration::process_withdraw_migration(
+            program_id,
+            accounts,
+            mint,
+            input,
+            output_tree_index,
+        ),
+    }
+}
+
+/// Accounts of claim instructions, see `build_claim_and_decompress_instruction`.
+pub(crate) struct ClaimAccountInfos<'a, 'info> {
+    pub claimant: &'a AccountInfo<'info>,
+    pub fee_payer: &'a AccountInfo<'info>,
+    pub associated_airdrop_pda: &'a AccountInfo<'info>,
+    pub ctoken_cpi_authority_pda: &'a AccountInfo<'info>,
+    pub light_system_program: &'a AccountInfo<'info>,
+    pub registered_program_pda: &'a AccountInfo<'info>,
+    pub noop_program: &'a AccountInfo<'info>,
+    pub account_compression_authority: &'a AccountInfo<'info>,
+    pub account_compression_program: &'a AccountInfo<'info>,
+    pub ctoken_program: &'a AccountInfo<'info>,
+    pub token_pool_pda: &'a AccountInfo<'info>,
+    pub decompress_destination: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub config: &'a AccountInfo<'info>,
+    pub fee_recipient: &'a AccountInfo<'info>,
+    /// Packed state trees and queues.
+    pub tree_accounts: &'a [AccountInfo<'info>],
+    /// Index in `tree_accounts` that new compressed accounts are written to.
+    pub output_tree_index: u8,
+    /// CPI context of the compressed token transfers, see
+    /// `process_claim_basket`.
+    pub cpi_context: Option<CompressedCpiContext>,
+}
+
+impl<'a, 'info> ClaimAccountInfos<'a, 'info> {
+    /// Number of accounts before the tree accounts.
+    pub const LEN: usize = 16;
+
+    /// Parses the claim accounts, there must be no accounts after the tree
+    /// accounts.
+    pub fn try_from_slice(
+        accounts: &'a [AccountInfo<'info>],
+        merkle_context: Option<&PackedMerkleContext>,
+        output_tree_index: u8,
+    ) -> Result<Self, ProgramError> {
+        let (accounts, remaining_accounts) =
+            Self::split(accounts, merkle_context, output_tree_index)?;
+        if !remaining_accounts.is_empty() {
+            msg!("Unexpected {} trailing accounts", remaining_accounts.len());
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(accounts)
+    }
+
+    /// Returns the claim accounts and the accounts following them.
+    ///
+    /// The tree accounts are the state trees and queues up to the highest
+    /// index referenced by the input `merkle_context` and `output_tree_index`.
+    pub fn split(
+        accounts: &'a [AccountInfo<'info>],
+        merkle_context: Option<&PackedMerkleContext>,
+        output_tree_index: u8,
+    ) -> Result<(Self, &'a [AccountInfo<'info>]), ProgramError> {
+        let num_tree_accounts = merkle_context
+            .map(|context| {
+                context
+                    .merkle_tree_pubkey_index
+                    .max(context.queue_pubkey_index)
+            })
+            .unwrap_or_default()
+            .max(output_tree_index) as usize
+            + 1;
+        Self::split_at(accounts, num_tree_accounts, output_tree_index)
+    }
+
+    /// Returns the claim accounts with `num_tree_accounts` tree accounts and
+    /// the accounts following them.
+    pub fn split_at(
+        accounts: &'a [AccountInfo<'info>],
+        num_tree_accounts: usize,
+        output_tree_index: u8,
+    ) -> Result<(Self, &'a [AccountInfo<'info>]), ProgramError> {
+        if accounts.len() < Self::LEN + num_tree_accounts {
+            msg!(
+                "Expected at least {} accounts, got {}",
+                Self::LEN + num_tree_accounts,
+                accounts.len()
+            );
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (accounts, remaining_accounts) = accounts.split_at(Self::LEN);
+        let (tree_accounts, remaining_accounts) = remaining_accounts.split_at(num_tree_accounts);
+        let accounts = Self {
+            claimant: &accounts[0],
+            fee_payer: &accounts[1],
+            associated_airdrop_pda: &accounts[2],
+            ctoken_cpi_authority_pda: &accounts[3],
+            light_system_program: &accounts[4],
+            registered_program_pda: &accounts[5],
+            noop_program: &accounts[6],
+            account_compression_authority: &accounts[7],
+            account_compression_program: &accounts[8],
+            ctoken_program: &accounts[9],
+            token_pool_pda: &accounts[10],
+            decompress_destination: &accounts[11],
+            token_program: &accounts[12],
+            system_program: &accounts[13],
+            config: &accounts[14],
+            fee_recipient: &accounts[15],
+            tree_accounts,
+            output_tree_index,
+            cpi_context: None,
+        };
+        // CHECK:
+        if !accounts.claimant.is_signer {
+            msg!("Claimant must be a signer");
+            accounts.claimant.key.log();
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        // CHECK:
+        if !accounts.fee_payer.is_signer {
+            msg!("Fee payer must be a signer");
+            accounts.fee_payer.key.log();
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        // CHECK:
+        if accounts.ctoken_program.key != &CTOKEN_PROGRAM_ID {
+            msg!("Invalid compressed token program.",);
+            accounts.ctoken_program.key.log();
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok((accounts, remaining_accounts))
+    }
+
+    /// Compressed token CPI accounts, with the associated airdrop PDA as authority.
+    pub fn light_cpi_accounts(&self) -> CtokenCpiAccounts<'a, 'info> {
+        CtokenCpiAccounts {
+            fee_payer: self.fee_payer.clone(),
+            authority: self.associated_airdrop_pda.clone(),
+            cpi_authority_pda: self.ctoken_cpi_authority_pda.clone(),
+            light_system_program: self.light_system_program.clone(),
+            registered_program_pda: self.registered_program_pda.clone(),
+            noop_program: self.noop_program.clone(),
+            account_compression_authority: self.account_compression_authority.clone(),
+            account_compression_program: self.account_compression_program.clone(),
+            self_program: self.ctoken_program.clone(),
+            token_pool_pda: self.token_pool_pda.clone(),
+            decompress_destination: self.decompress_destination.clone(),
+            token_program: self.token_program.clone(),
+            system_program: self.system_program.clone(),
+            tree_accounts: self.tree_accounts,
+            cpi_context: self.cpi_context,
+        }
     }
 }
 
@@ -61,155 +566,775 @@ fn process_claim(
     mint: Pubkey,
     unlock_slot: u64,
     bump_seed: u8,
+    fee: Option<ClaimFee>,
+    claim_amount: Option<u64>,
+    output_tree_index: u8,
+    attestation: Option<Attestation>,
+    denylist: Option<DenylistCheck>,
+    expiry_slot: Option<u64>,
+    decay: Option<ClaimDecay>,
 ) -> ProgramResult {
-    let claimant_info = &accounts[0];
-    let fee_payer_info = &accounts[1];
-    let associated_airdrop_pda_info = &accounts[2];
-    let ctoken_cpi_authority_pda_info = &accounts[3];
-    let light_system_program_info = &accounts[4];
-    let registered_program_pda_info = &accounts[5];
-    let noop_program_info = &accounts[6];
-    let account_compression_authority_info = &accounts[7];
-    let account_compression_program_info = &accounts[8];
-    let ctoken_program_info = &accounts[9];
-    let token_pool_pda_info = &accounts[10];
-    let decompress_destination_info = &accounts[11];
-    let token_program_info = &accounts[12];
-    let system_program_info = &accounts[13];
-    let state_tree_info = &accounts[14];
-    let queue_info = &accounts[15];
-
-    if accounts.len() != 16 {
-        msg!("Expected 16 accounts, got {}", accounts.len());
-        return Err(ProgramError::NotEnoughAccountKeys);
+    // the tree accounts also hold the CPI context account and the denylist
+    // entry of denylist checks.
+    let num_tree_accounts = [
+        merkle_context.merkle_tree_pubkey_index,
+        merkle_context.queue_pubkey_index,
+        output_tree_index,
+    ]
+    .into_iter()
+    .chain(denylist.iter().flat_map(|denylist| {
+        let entry_index = match denylist.entry {
+            DenylistEntryProof::Absent {
+                address_tree_index, ..
+            } => address_tree_index,
+            DenylistEntryProof::Existing { merkle_context, .. } => merkle_context
+                .merkle_tree_pubkey_index
+                .max(merkle_context.queue_pubkey_index),
+        };
+        [denylist.cpi_context_index, entry_index]
+    }))
+    .max()
+    .unwrap_or_default() as usize
+        + 1;
+    let (accounts, remaining_accounts) =
+        ClaimAccountInfos::split_at(accounts, num_tree_accounts, output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+    if let Some(expiry_slot) = expiry_slot {
+        check_not_expired(expiry_slot)?;
     }
-
-    // CHECK:
-    if !claimant_info.is_signer {
-        msg!("Claimant must be a signer");
-        claimant_info.key.log();
-        return Err(ProgramError::MissingRequiredSignature);
+    // nothing is left to claim once the decay ends.
+    if let Some(decay) = &decay {
+        check_not_expired(decay.end_slot)?;
     }
-    // CHECK:
-    if !fee_payer_info.is_signer {
-        msg!("Fee payer must be a signer");
-        fee_payer_info.key.log();
-        return Err(ProgramError::MissingRequiredSignature);
+
+    let mut remaining_accounts = remaining_accounts.iter();
+    if let Some(attestation) = &attestation {
+        let instructions_sysvar = remaining_accounts
+            .next()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        check_attestation(
+            attestation,
+            accounts.claimant.key,
+            accounts.associated_airdrop_pda.key,
+            instructions_sysvar,
+        )?;
     }
-    // CHECK:
-    if ctoken_program_info.key != &CTOKEN_PROGRAM_ID {
-        msg!("Invalid compressed token program.",);
-        ctoken_program_info.key.log();
+    let denylist_accounts = match &denylist {
+        Some(_) => {
+            let (Some(cpi_authority_pda), Some(claim_program)) =
+                (remaining_accounts.next(), remaining_accounts.next())
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            Some((cpi_authority_pda, claim_program))
+        }
+        None => None,
+    };
+    if remaining_accounts.len() > 0 {
+        msg!("Unexpected {} trailing accounts", remaining_accounts.len());
         return Err(ProgramError::InvalidArgument);
     }
 
-    let ctoken_account =
-        get_compressed_token_account_info(merkle_context, root_index, amount, lamports);
-
-    // CHECK:
-    let current_slot = Clock::get()?.slot;
-    if current_slot < unlock_slot {
-        msg!(
-            "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
-            current_slot,
-            unlock_slot
-        );
-        return Err(ClaimError::TokensLocked.into());
-    }
-
-    let light_cpi_accounts = CompressedTokenDecompressCpiAccounts {
-        fee_payer: fee_payer_info.clone(),
-        authority: associated_airdrop_pda_info.clone(),
-        cpi_authority_pda: ctoken_cpi_authority_pda_info.clone(),
-        light_system_program: light_system_program_info.clone(),
-        registered_program_pda: registered_program_pda_info.clone(),
-        noop_program: noop_program_info.clone(),
-        account_compression_authority: account_compression_authority_info.clone(),
-        account_compression_program: account_compression_program_info.clone(),
-        self_program: ctoken_program_info.clone(),
-        token_pool_pda: token_pool_pda_info.clone(),
-        decompress_destination: decompress_destination_info.clone(),
-        token_program: token_program_info.clone(),
-        system_program: system_program_info.clone(),
-        state_merkle_tree: state_tree_info.clone(),
-        queue: queue_info.clone(),
+    let seeds = AirdropSeeds::new(accounts.claimant.key, &mint, unlock_slot)
+        .with_fee(fee.as_ref())
+        .with_attestor(
+            attestation
+                .as_ref()
+                .map(|attestation| &attestation.attestor),
+        )
+        .with_denylist(denylist.as_ref().map(|denylist| &denylist.denylist))
+        .with_expiry_slot(expiry_slot)
+        .with_decay(decay.as_ref());
+    // with a denylist the transfer is stored in the CPI context and executed
+    // by the denylist check, under its proof.
+    let transfer_accounts = ClaimAccountInfos {
+        cpi_context: denylist.map(|denylist| CompressedCpiContext {
+            set_context: false,
+            first_set_context: true,
+            cpi_context_account_index: denylist.cpi_context_index,
+        }),
+        ..accounts
     };
-    check_pda_and_decompress_token(
+    let transfer_proof = if denylist.is_some() { None } else { proof };
+    claim_airdrop(
         program_id,
-        light_cpi_accounts,
-        ctoken_account,
-        &proof,
-        claimant_info.clone(),
+        &transfer_accounts,
+        &seeds,
+        bump_seed,
+        fee,
+        get_compressed_token_account_info(merkle_context, root_index, amount, lamports),
+        claim_amount,
+        transfer_proof,
         mint,
         unlock_slot,
-        bump_seed,
-    )
+        None,
+        decay.as_ref(),
+    )?;
+
+    if let (Some(denylist), Some((cpi_authority_pda, claim_program))) =
+        (&denylist, denylist_accounts)
+    {
+        denylist::check_not_denied(
+            denylist,
+            accounts.claimant.key,
+            proof,
+            output_tree_index,
+            &InvokeCpiAccounts {
+                fee_payer: accounts.fee_payer.clone(),
+                cpi_authority_pda: cpi_authority_pda.clone(),
+                light_system_program: accounts.light_system_program.clone(),
+                registered_program_pda: accounts.registered_program_pda.clone(),
+                noop_program: accounts.noop_program.clone(),
+                account_compression_authority: accounts.account_compression_authority.clone(),
+                account_compression_program: accounts.account_compression_program.clone(),
+                invoking_program: claim_program.clone(),
+                system_program: accounts.system_program.clone(),
+                tree_accounts: accounts.tree_accounts,
+                cpi_context_index: Some(denylist.cpi_context_index),
+            },
+        )?;
+    }
+    Ok(())
 }
 
 #[allow(clippy::too_many_arguments)]
-fn check_pda_and_decompress_token(
-    claim_program: &Pubkey,
-    light_cpi_accounts: CompressedTokenDecompressCpiAccounts,
-    compressed_token_account: InputTokenDataWithContext,
-    proof: &Option<CompressedProof>,
-    claimant: AccountInfo<'_>,
+fn process_claim_multisig(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
     mint: Pubkey,
-    slot: u64,
+    unlock_slot: u64,
     bump_seed: u8,
+    fee: Option<ClaimFee>,
+    multisig: ClaimMultisig,
+    output_tree_index: u8,
 ) -> ProgramResult {
-    let claimant_bytes = claimant.key.to_bytes();
-    let slot_bytes = slot.to_le_bytes();
-    let mint_bytes = mint.to_bytes();
-
-    let seeds = &[
-        &claimant_bytes[..32],
-        &mint_bytes[..32],
-        &slot_bytes[..8],
-        &[bump_seed],
-    ];
+    let (accounts, signer_accounts) =
+        ClaimAccountInfos::split(accounts, Some(&merkle_context), output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
 
-    check_claim_pda(seeds, claim_program, light_cpi_accounts.authority.key)?;
+    // CHECK:
+    if !multisig.is_valid() {
+        msg!(
+            "Invalid multisig: threshold {} of {} signers.",
+            multisig.threshold,
+            multisig.signers.len()
+        );
+        return Err(ClaimError::InvalidMultisig.into());
+    }
+    // CHECK:
+    let signed = multisig.count_signed(
+        std::iter::once(accounts.claimant)
+            .chain(signer_accounts)
+            .filter(|account| account.is_signer)
+            .map(|account| account.key),
+    );
+    if signed < multisig.threshold as usize {
+        msg!(
+            "Not enough multisig signers: {} of {} required.",
+            signed,
+            multisig.threshold
+        );
+        return Err(ClaimError::NotEnoughSigners.into());
+    }
 
-    let instruction = cpi::instruction::decompress(
-        &mint,
-        vec![compressed_token_account],
+    let seeds = AirdropSeeds::new_multisig(&multisig, &mint, unlock_slot).with_fee(fee.as_ref());
+    claim_airdrop(
+        program_id,
+        &accounts,
+        &seeds,
+        bump_seed,
+        fee,
+        get_compressed_token_account_info(merkle_context, root_index, amount, lamports),
+        None,
         proof,
-        &light_cpi_accounts,
+        mint,
+        unlock_slot,
+        None,
         None,
     )?;
+    Ok(())
+}
 
-    let signers_seeds: &[&[&[u8]]] = &[&seeds[..]];
-    invoke_signed(
-        &instruction,
-        &[
-            light_cpi_accounts.fee_payer,
-            light_cpi_accounts.authority,
-            light_cpi_accounts.cpi_authority_pda,
-            light_cpi_accounts.light_system_program,
-            light_cpi_accounts.registered_program_pda,
-            light_cpi_accounts.noop_program,
-            light_cpi_accounts.account_compression_authority,
-            light_cpi_accounts.account_compression_program,
-            light_cpi_accounts.self_program,
-            light_cpi_accounts.token_pool_pda,
-            light_cpi_accounts.decompress_destination,
-            light_cpi_accounts.token_program,
-            light_cpi_accounts.system_program,
-            light_cpi_accounts.state_merkle_tree,
-            light_cpi_accounts.queue,
-        ][..],
-        signers_seeds,
+/// Claims an airdrop with early unlock terms, before the unlock slot at the
+/// cost of the early unlock penalty.
+#[allow(clippy::too_many_arguments)]
+fn process_early_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    early_unlock: EarlyUnlock,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let accounts =
+        ClaimAccountInfos::try_from_slice(accounts, Some(&merkle_context), output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+    check_penalty(&early_unlock)?;
+
+    let seeds = AirdropSeeds::new(accounts.claimant.key, &mint, unlock_slot)
+        .with_fee(fee.as_ref())
+        .with_early_unlock(Some(&early_unlock));
+    claim_airdrop(
+        program_id,
+        &accounts,
+        &seeds,
+        bump_seed,
+        fee,
+        get_compressed_token_account_info(merkle_context, root_index, amount, lamports),
+        None,
+        proof,
+        mint,
+        unlock_slot,
+        Some(&early_unlock),
+        None,
     )?;
     Ok(())
 }
 
-fn check_claim_pda(
+#[allow(clippy::too_many_arguments)]
+fn process_claim_with_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    hook: Pubkey,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let (accounts, remaining_accounts) =
+        ClaimAccountInfos::split(accounts, Some(&merkle_context), output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+
+    let (hook_program, hook_accounts) = remaining_accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    // CHECK:
+    if *hook_program.key != hook || hook == *program_id {
+        msg!(
+            "Invalid hook program provided. Expected: {}. Found: {}.",
+            hook,
+            hook_program.key
+        );
+        return Err(ClaimError::InvalidHookProgram.into());
+    }
+
+    let seeds = AirdropSeeds::new(accounts.claimant.key, &mint, unlock_slot)
+        .with_fee(fee.as_ref())
+        .with_hook(Some(&hook));
+    let claimed_amount = claim_airdrop(
+        program_id,
+        &accounts,
+        &seeds,
+        bump_seed,
+        fee,
+        get_compressed_token_account_info(merkle_context, root_index, amount, lamports),
+        None,
+        proof,
+        mint,
+        unlock_slot,
+        None,
+        None,
+    )?;
+
+    hook::invoke_hook(
+        hook_program,
+        hook_accounts,
+        &ClaimHookData {
+            claimant: *accounts.claimant.key,
+            mint,
+            amount: claimed_amount,
+            decompress_destination: *accounts.decompress_destination.key,
+        },
+    )
+}
+
+/// Claims the airdrop of each mint in `claims` to its decompress destination.
+/// The first claim uses the airdrop PDA, token pool and decompress
+/// destination of the claim accounts, further claims take them from the
+/// accounts after the tree accounts.
+fn process_claim_basket(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    unlock_slot: u64,
+    claims: Vec<BasketClaim>,
+    cpi_context_index: u8,
+    output_tree_index: u8,
+) -> ProgramResult {
+    // CHECK:
+    if claims.is_empty() {
+        msg!("Basket claims no mints.");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    // the tree accounts reach up to the highest index of any input and the
+    // CPI context.
+    let num_tree_accounts = claims
+        .iter()
+        .map(|claim| {
+            claim
+                .merkle_context
+                .merkle_tree_pubkey_index
+                .max(claim.merkle_context.queue_pubkey_index)
+        })
+        .chain([output_tree_index, cpi_context_index])
+        .max()
+        .unwrap_or_default() as usize
+        + 1;
+    let (accounts, remaining_accounts) =
+        ClaimAccountInfos::split_at(accounts, num_tree_accounts, output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+
+    // CHECK:
+    if remaining_accounts.len() != (claims.len() - 1) * 3 {
+        msg!(
+            "Expected {} accounts after the tree accounts, got {}",
+            (claims.len() - 1) * 3,
+            remaining_accounts.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    let mint_accounts = std::iter::once([
+        accounts.associated_airdrop_pda,
+        accounts.token_pool_pda,
+        accounts.decompress_destination,
+    ])
+    .chain(
+        remaining_accounts
+            .chunks_exact(3)
+            .map(|chunk| [&chunk[0], &chunk[1], &chunk[2]]),
+    );
+
+    let last = claims.len() - 1;
+    for (i, (claim, [associated_airdrop_pda, token_pool_pda, decompress_destination])) in
+        claims.into_iter().zip(mint_accounts).enumerate()
+    {
+        let cpi_context = (last > 0).then_some(CompressedCpiContext {
+            set_context: i > 0 && i < last,
+            first_set_context: i == 0,
+            cpi_context_account_index: cpi_context_index,
+        });
+        let mint_claim_accounts = ClaimAccountInfos {
+            associated_airdrop_pda,
+            token_pool_pda,
+            decompress_destination,
+            cpi_context,
+            ..accounts
+        };
+        let terms = claim.terms;
+        // CHECK:
+        if terms.hook.is_some() || terms.attestor.is_some() || terms.denylist.is_some() {
+            msg!("Basket claims don't support hook, attestor or denylist terms.");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if let Some(expiry_slot) = terms.expiry_slot {
+            check_not_expired(expiry_slot)?;
+        }
+        // nothing is left to claim once the decay ends.
+        if let Some(decay) = &terms.decay {
+            check_not_expired(decay.end_slot)?;
+        }
+        if let Some(early_unlock) = &terms.early_unlock {
+            check_penalty(early_unlock)?;
+        }
+
+        let seeds =
+            AirdropSeeds::new(accounts.claimant.key, &claim.mint, unlock_slot).with_terms(&terms);
+        claim_airdrop(
+            program_id,
+            &mint_claim_accounts,
+            &seeds,
+            claim.bump_seed,
+            terms.fee,
+            get_compressed_token_account_info(
+                claim.merkle_context,
+                claim.root_index,
+                claim.amount,
+                claim.lamports,
+            ),
+            None,
+            // only the last transfer executes and verifies the proof.
+            proof.filter(|_| i == last),
+            claim.mint,
+            unlock_slot,
+            terms.early_unlock.as_ref(),
+            terms.decay.as_ref(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Checks that `attestation` has not expired and that the transaction
+/// verifies the attestor's signature for `claimant` and `airdrop_pda`.
+fn check_attestation(
+    attestation: &Attestation,
+    claimant: &Pubkey,
+    airdrop_pda: &Pubkey,
+    instructions_sysvar: &AccountInfo,
+) -> ProgramResult {
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot >= attestation.expiry_slot {
+        msg!(
+            "Attestation expired at slot {}, current slot is {}.",
+            attestation.expiry_slot,
+            current_slot
+        );
+        return Err(ClaimError::AttestationExpired.into());
+    }
+    // CHECK:
+    attestation::verify_attestation(
+        instructions_sysvar,
+        &attestation.attestor,
+        &attestation.message(claimant, airdrop_pda),
+    )
+}
+
+/// Checks that the early unlock penalty is at most `MAX_BPS`.
+fn check_penalty(early_unlock: &EarlyUnlock) -> ProgramResult {
+    // CHECK:
+    if early_unlock.penalty_bps > ClaimFee::MAX_BPS {
+        msg!(
+            "Penalty of {} bps exceeds {} bps.",
+            early_unlock.penalty_bps,
+            ClaimFee::MAX_BPS
+        );
+        return Err(ClaimError::InvalidPenalty.into());
+    }
+    Ok(())
+}
+
+/// Checks that the claim window closing at `expiry_slot` is still open.
+fn check_not_expired(expiry_slot: u64) -> ProgramResult {
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot >= expiry_slot {
+        msg!(
+            "Claim window closed at slot {}, current slot is {}.",
+            expiry_slot,
+            current_slot
+        );
+        return Err(ClaimError::ClaimExpired.into());
+    }
+    Ok(())
+}
+
+/// Checks the unlock slot and airdrop PDA, charges the fee and decompresses
+/// `claim_amount` of the airdrop, all of it if `None`, to the decompress
+/// destination. The remainder stays compressed in the airdrop PDA.
+///
+/// With `early_unlock`, locked airdrops can be claimed and the early unlock
+/// penalty of the claimed tokens net of fees is compressed to the treasury.
+/// With `decay`, the forfeited share of the claimed tokens net of fees is
+/// compressed to the decay treasury.
+///
+/// Returns the decompressed amount.
+#[allow(clippy::too_many_arguments)]
+fn claim_airdrop(
+    program_id: &Pubkey,
+    accounts: &ClaimAccountInfos,
+    seeds: &AirdropSeeds,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    ctoken_account: InputTokenDataWithContext,
+    claim_amount: Option<u64>,
+    proof: Option<CompressedProof>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    early_unlock: Option<&EarlyUnlock>,
+    decay: Option<&ClaimDecay>,
+) -> Result<u64, ProgramError> {
+    let amount = ctoken_account.amount;
+    let claim_amount = claim_amount.unwrap_or(amount);
+
+    // CHECK:
+    if claim_amount == 0 || claim_amount > amount {
+        msg!(
+            "Invalid claim amount {}, the airdrop holds {}.",
+            claim_amount,
+            amount
+        );
+        return Err(ClaimError::InvalidClaimAmount.into());
+    }
+
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot < unlock_slot && early_unlock.is_none() {
+        msg!(
+            "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
+            current_slot,
+            unlock_slot
+        );
+        return Err(ClaimError::TokensLocked.into());
+    }
+
+    // CHECK:
+    let bump = [bump_seed];
+    let mut signer_seeds = seeds.seeds();
+    signer_seeds.push(&bump);
+    check_claim_pda(
+        &signer_seeds,
+        program_id,
+        accounts.associated_airdrop_pda.key,
+    )?;
+
+    let mut outputs = Vec::new();
+    if let Some(fee) = fee {
+        // CHECK:
+        if *accounts.fee_recipient.key != fee.recipient {
+            msg!(
+                "Invalid fee recipient provided. Expected: {}. Found: {}.",
+                fee.recipient,
+                accounts.fee_recipient.key
+            );
+            return Err(ClaimError::InvalidFeeRecipient.into());
+        }
+        match fee.amount {
+            FeeAmount::Lamports(fee_lamports) => invoke(
+                &system_instruction::transfer(accounts.claimant.key, &fee.recipient, fee_lamports),
+                &[
+                    accounts.claimant.clone(),
+                    accounts.fee_recipient.clone(),
+                    accounts.system_program.clone(),
+                ],
+            )?,
+            FeeAmount::Bps(bps) => {
+                // CHECK:
+                if bps > ClaimFee::MAX_BPS {
+                    msg!("Fee of {} bps exceeds {} bps.", bps, ClaimFee::MAX_BPS);
+                    return Err(ClaimError::InvalidFee.into());
+                }
+                let token_fee = fee.token_fee(claim_amount);
+                if token_fee > 0 {
+                    outputs.push(PackedTokenTransferOutputData {
+                        owner: fee.recipient,
+                        amount: token_fee,
+                        lamports: None,
+                        merkle_tree_index: accounts.output_tree_index,
+                        tlv: None,
+                    });
+                }
+            }
+        }
+    }
+
+    let token_fee = fee.map_or(0, |fee| fee.token_fee(claim_amount));
+    if let Some(early_unlock) = early_unlock {
+        let penalty = early_unlock.penalty(claim_amount - token_fee, current_slot, unlock_slot);
+        if penalty > 0 {
+            msg!("Early unlock penalty of {} tokens.", penalty);
+            outputs.push(PackedTokenTransferOutputData {
+                owner: early_unlock.treasury,
+                amount: penalty,
+                lamports: None,
+                merkle_tree_index: accounts.output_tree_index,
+                tlv: None,
+            });
+        }
+    }
+    if let Some(decay) = decay {
+        let forfeited_amount = decay.forfeited_amount(claim_amount - token_fee, current_slot);
+        if forfeited_amount > 0 {
+            msg!("Forfeited {} tokens to decay.", forfeited_amount);
+            outputs.push(PackedTokenTransferOutputData {
+                owner: decay.treasury,
+                amount: forfeited_amount,
+                lamports: None,
+                merkle_tree_index: accounts.output_tree_index,
+                tlv: None,
+            });
+        }
+    }
+
+    // the unclaimed remainder returns to the airdrop PDA.
+    if claim_amount < amount {
+        outputs.push(PackedTokenTransferOutputData {
+            owner: *accounts.associated_airdrop_pda.key,
+            amount: amount - claim_amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        });
+    }
+
+    let decompress_amount = amount - outputs.iter().map(|output| output.amount).sum::<u64>();
+    ctoken::transfer_and_decompress(
+        &mint,
+        vec![ctoken_account],
+        outputs,
+        proof,
+        &accounts.light_cpi_accounts(),
+        &[&signer_seeds],
+    )?;
+    Ok(decompress_amount)
+}
+
+/// Moves a locked airdrop to the airdrop PDA of `new_claimant` with the same
+/// mint, unlock slot and campaign terms. Airdrops with a denylist are rejected,
+/// so a denied claimant can't move its airdrop to another wallet.
+#[allow(clippy::too_many_arguments)]
+fn process_transfer_beneficiary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    terms: AirdropTerms,
+    new_claimant: Pubkey,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let accounts =
+        ClaimAccountInfos::try_from_slice(accounts, Some(&merkle_context), output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot >= unlock_slot {
+        msg!(
+            "Tokens are unlocked since slot {}, claim them instead.",
+            unlock_slot
+        );
+        return Err(ClaimError::TokensUnlocked.into());
+    }
+    // CHECK:
+    if terms.denylist.is_some() {
+        msg!("Airdrops with a denylist can't be transferred.");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // CHECK:
+    if let Some(expiry_slot) = terms.expiry_slot {
+        check_not_expired(expiry_slot)?;
+    }
+
+    // CHECK:
+    let seeds = AirdropSeeds::new(accounts.claimant.key, &mint, unlock_slot).with_terms(&terms);
+    let bump = [bump_seed];
+    let mut signer_seeds = seeds.seeds();
+    signer_seeds.push(&bump);
+    check_claim_pda(
+        &signer_seeds,
+        program_id,
+        accounts.associated_airdrop_pda.key,
+    )?;
+
+    let (new_airdrop_pda, _) = AirdropSeeds::new(&new_claimant, &mint, unlock_slot)
+        .with_terms(&terms)
+        .find_program_address();
+    msg!("Transferring airdrop to {}.", new_airdrop_pda);
+
+    ctoken::transfer_and_decompress(
+        &mint,
+        vec![get_compressed_token_account_info(
+            merkle_context,
+            root_index,
+            amount,
+            lamports,
+        )],
+        vec![PackedTokenTransferOutputData {
+            owner: new_airdrop_pda,
+            amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        }],
+        proof,
+        &accounts.light_cpi_accounts(),
+        &[&signer_seeds],
+    )
+}
+
+/// Writes the `Claimable` amount of an airdrop or grant PDA holding `amount`
+/// as return data.
+fn process_get_claimable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    source: ClaimSource,
+    amount: u64,
+    bump_seed: u8,
+) -> ProgramResult {
+    let [associated_airdrop_pda, config] = accounts else {
+        msg!("Expected 2 accounts, got {}", accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // CHECK:
+    let bump = [bump_seed];
+    match &source {
+        ClaimSource::Airdrop {
+            claimant,
+            mint,
+            unlock_slot,
+            fee,
+            hook,
+            attestor,
+            denylist,
+            expiry_slot,
+            early_unlock,
+            decay,
+        } => {
+            let seeds = AirdropSeeds::new(claimant, mint, *unlock_slot)
+                .with_fee(fee.as_ref())
+                .with_hook(hook.as_ref())
+                .with_attestor(attestor.as_ref())
+                .with_denylist(denylist.as_ref())
+                .with_expiry_slot(*expiry_slot)
+                .with_early_unlock(early_unlock.as_ref())
+                .with_decay(decay.as_ref());
+            let mut signer_seeds = seeds.seeds();
+            signer_seeds.push(&bump);
+            check_claim_pda(&signer_seeds, program_id, associated_airdrop_pda.key)?;
+        }
+        ClaimSource::Grant(grant) => check_claim_pda(
+            &[GRANT_SEED, &grant.commitment(), &bump],
+            program_id,
+            associated_airdrop_pda.key,
+        )?,
+    }
+
+    let mut claimable = source.claimable(amount, Clock::get()?.slot);
+    claimable.paused = load_config(program_id, config)?.paused;
+    set_return_data(&borsh::to_vec(&claimable)?);
+    Ok(())
+}
+
+pub(crate) fn check_not_paused(program_id: &Pubkey, config_info: &AccountInfo) -> ProgramResult {
+    // CHECK:
+    if load_config(program_id, config_info)?.paused {
+        msg!("Claims are paused.");
+        return Err(ClaimError::ClaimsPaused.into());
+    }
+    Ok(())
+}
+
+pub(crate) fn check_claim_pda(
     seeds: &[&[u8]],
     claim_program: &Pubkey,
     airdrop_account: &Pubkey,
 ) -> Result<(), ProgramError> {
     let derived_pda =
-        Pubkey::create_program_address(seeds, claim_program).expect("Invalid PDA seeds.");
+        Pubkey::create_program_address(seeds, claim_program).map_err(|_| ClaimError::InvalidPDA)?;
 
     if derived_pda != *airdrop_account {
         msg!(
@@ -222,3 +1347,233 @@ fn check_claim_pda(
 
     Ok(())
 }
+
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+) -> ProgramResult {
+    if accounts.len() != 4 {
+        msg!("Expected 4 accounts, got {}", accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let upgrade_authority_info = &accounts[0];
+    let config_info = &accounts[1];
+    let system_program_info = &accounts[2];
+    let program_data_info = &accounts[3];
+
+    // CHECK:
+    if !upgrade_authority_info.is_signer {
+        msg!("Upgrade authority must be a signer");
+        upgrade_authority_info.key.log();
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // CHECK:
+    if upgrade_authority(program_id, program_data_info)? != Some(*upgrade_authority_info.key) {
+        msg!("Only the upgrade authority can initialize the config.");
+        upgrade_authority_info.key.log();
+        return Err(ClaimError::Unauthorized.into());
+    }
+    // CHECK:
+    let bump_seed = check_config_pda(program_id, config_info)?;
+
+    // Fails if the config already exists.
+    create_pda_account(
+        program_id,
+        upgrade_authority_info,
+        config_info,
+        system_program_info,
+        ClaimConfig::LEN,
+        &[CONFIG_SEED, &[bump_seed]],
+    )?;
+
+    let config = ClaimConfig {
+        admin,
+        pending_admin: None,
+        paused: false,
+        bump_seed,
+    };
+    store_config(config_info, &config)
+}
+
+/// Returns the upgrade authority of the claim program from its program data
+/// account, `None` if the program is immutable.
+fn upgrade_authority(
+    program_id: &Pubkey,
+    program_data_info: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    // CHECK:
+    if *program_data_info.key != bpf_loader_upgradeable::get_program_data_address(program_id)
+        || *program_data_info.owner != bpf_loader_upgradeable::id()
+    {
+        msg!("Invalid program data account.");
+        program_data_info.key.log();
+        return Err(ProgramError::InvalidArgument);
+    }
+    // bincode `UpgradeableLoaderState::ProgramData`: variant index 3, slot and
+    // the optional upgrade authority.
+    let data = program_data_info.try_borrow_data()?;
+    match data.get(..UpgradeableLoaderState::size_of_programdata_metadata()) {
+        Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 0, ..]) => Ok(None),
+        Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 1, authority @ ..]) => {
+            Ok(Some(Pubkey::try_from(authority).unwrap()))
+        }
+        _ => {
+            msg!("Invalid program data account.");
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
+
+/// Creates the PDA `account_info` of the claim program with `space` bytes,
+/// paid by `payer_info`. Unlike `create_account` alone this succeeds if the
+/// PDA was already funded with lamports, so nobody can block its creation by
+/// transferring lamports to it. Fails if the account already exists.
+pub(crate) fn create_pda_account<'info>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'info>,
+    account_info: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = account_info.lamports();
+    if lamports == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                account_info.key,
+                rent,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[signer_seeds],
+        );
+    }
+    if lamports < rent {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, rent - lamports),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, space as u64),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account_info.key, program_id),
+        &[account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )
+}
+
+fn process_set_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+) -> ProgramResult {
+    let (config_info, mut config) = load_config_as_admin(program_id, accounts)?;
+    config.paused = paused;
+    msg!("Claims paused: {}", paused);
+    store_config(config_info, &config)
+}
+
+fn process_transfer_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_admin: Pubkey,
+) -> ProgramResult {
+    let (config_info, mut config) = load_config_as_admin(program_id, accounts)?;
+    config.pending_admin = Some(new_admin);
+    store_config(config_info, &config)
+}
+
+fn process_accept_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    if accounts.len() != 2 {
+        msg!("Expected 2 accounts, got {}", accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let pending_admin_info = &accounts[0];
+    let config_info = &accounts[1];
+    let mut config = load_config(program_id, config_info)?;
+
+    // CHECK:
+    if !pending_admin_info.is_signer || config.pending_admin != Some(*pending_admin_info.key) {
+        msg!("Pending admin must be a signer");
+        pending_admin_info.key.log();
+        return Err(ClaimError::Unauthorized.into());
+    }
+
+    config.admin = *pending_admin_info.key;
+    config.pending_admin = None;
+    store_config(config_info, &config)
+}
+
+/// Checks that accounts are (admin signer, config PDA) and returns the config.
+fn load_config_as_admin<'a, 'info>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a AccountInfo<'info>, ClaimConfig), ProgramError> {
+    if accounts.len() != 2 {
+        msg!("Expected 2 accounts, got {}", accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let admin_info = &accounts[0];
+    let config_info = &accounts[1];
+    let config = load_config(program_id, config_info)?;
+
+    // CHECK:
+    if !admin_info.is_signer || config.admin != *admin_info.key {
+        msg!("Admin must be a signer");
+        admin_info.key.log();
+        return Err(ClaimError::Unauthorized.into());
+    }
+    Ok((config_info, config))
+}
+
+fn load_config(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+) -> Result<ClaimConfig, ProgramError> {
+    // CHECK:
+    if config_info.owner != program_id {
+        msg!("Config account is not owned by the claim program.");
+        config_info.key.log();
+        return Err(ClaimError::InvalidConfig.into());
+    }
+    // CHECK:
+    check_config_pda(program_id, config_info)?;
+    ClaimConfig::deserialize(&mut &config_info.try_borrow_data()?[..])
+        .map_err(|_| ClaimError::InvalidConfig.into())
+}
+
+/// Checks that `config_info` is the config PDA at its canonical bump seed,
+/// so there is a single config PDA. Returns the bump seed.
+fn check_config_pda(program_id: &Pubkey, config_info: &AccountInfo) -> Result<u8, ProgramError> {
+    let (derived_pda, bump_seed) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+    if derived_pda != *config_info.key {
+        msg!(
+            "Invalid config PDA provided. Expected: {}. Found: {}.",
+            derived_pda,
+            config_info.key
+        );
+        return Err(ClaimError::InvalidConfig.into());
+    }
+    Ok(bump_seed)
+}
+
+fn store_config(config_info: &AccountInfo, config: &ClaimConfig) -> ProgramResult {
+    config.serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
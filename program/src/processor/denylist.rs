@@ -0,0 +1,289 @@
+//! Denylists of campaign authorities.
+//!
+//! A denylist entry is a compressed account of the claim program, its address
+//! is derived from the denylist authority and the denied address in the
+//! address tree of the denylist. Campaigns commit to the denylist in the
+//! airdrop PDA seeds, their claims prove that the entry of the claimant is
+//! absent or removed in the validity proof that also covers the compressed
+//! token input. Other claimants of the campaign are unaffected.
+//!
+//! Addresses can't be removed from an address tree, so removing an entry
+//! unsets `denied` and denying the address again sets it. Each update spends
+//! the entry and writes it again under the validity proof of the instruction.
+use crate::{
+    error::ClaimError,
+    light_system::{self, InvokeCpiAccounts},
+    pda::{denylist_entry_seed, derive_denylist_entry_address},
+    state::{Denylist, DenylistCheck, DenylistEntry, DenylistEntryProof},
+};
+use light_compressed_account::{
+    compressed_account::{CompressedAccount, CompressedAccountData, PackedMerkleContext},
+    instruction_data::{
+        compressed_proof::CompressedProof,
+        data::{
+            NewAddressParamsAssignedPacked, OutputCompressedAccountWithPackedContext,
+            PackedReadOnlyAddress,
+        },
+        with_readonly::InAccount,
+    },
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Denies `address` in the denylist of the signing authority with entries in
+/// `address_tree`. An `Absent` entry is created, an `Existing` one must be
+/// removed and is denied again.
+pub fn process_add_to_denylist(
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    address: Pubkey,
+    address_tree: Pubkey,
+    entry: DenylistEntryProof,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let light_cpi_accounts = denylist_cpi_accounts(accounts)?;
+    let denylist = Denylist {
+        authority: *light_cpi_accounts.fee_payer.key,
+        address_tree,
+    };
+
+    let (new_address_params, inputs) = match entry {
+        DenylistEntryProof::Absent {
+            address_tree_index,
+            address_root_index,
+        } => {
+            check_address_tree(&denylist, &light_cpi_accounts, address_tree_index)?;
+            let new_address_params = NewAddressParamsAssignedPacked {
+                seed: denylist_entry_seed(&denylist.authority, &address),
+                address_queue_account_index: address_tree_index,
+                address_merkle_tree_account_index: address_tree_index,
+                address_merkle_tree_root_index: address_root_index,
+                assigned_to_account: true,
+                assigned_account_index: 0,
+            };
+            (vec![new_address_params], Vec::new())
+        }
+        DenylistEntryProof::Existing {
+            merkle_context,
+            root_index,
+        } => (
+            Vec::new(),
+            vec![entry_input(
+                &denylist,
+                &address,
+                false,
+                merkle_context,
+                root_index,
+            )],
+        ),
+    };
+
+    light_system::invoke_cpi(
+        proof,
+        new_address_params,
+        inputs,
+        vec![entry_output(&denylist, &address, true, output_tree_index)],
+        Vec::new(),
+        &light_cpi_accounts,
+    )?;
+    msg!("Denied {}.", address);
+    Ok(())
+}
+
+/// Removes `address` from the denylist of the signing authority with entries
+/// in `address_tree`, its entry must be denied.
+pub fn process_remove_from_denylist(
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    address: Pubkey,
+    address_tree: Pubkey,
+    merkle_context: PackedMerkleContext,
+    root_index: u16,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let light_cpi_accounts = denylist_cpi_accounts(accounts)?;
+    let denylist = Denylist {
+        authority: *light_cpi_accounts.fee_payer.key,
+        address_tree,
+    };
+
+    light_system::invoke_cpi(
+        proof,
+        Vec::new(),
+        vec![entry_input(
+            &denylist,
+            &address,
+            true,
+            merkle_context,
+            root_index,
+        )],
+        vec![entry_output(&denylist, &address, false, output_tree_index)],
+        Vec::new(),
+        &light_cpi_accounts,
+    )?;
+    msg!("Removed {} from the denylist.", address);
+    Ok(())
+}
+
+/// Proves that the entry of `claimant` in `check.denylist` is absent or
+/// removed. The Light system CPI executes the compressed token transfer
+/// stored in the CPI context account, `proof` covers both.
+pub fn check_not_denied(
+    check: &DenylistCheck,
+    claimant: &Pubkey,
+    proof: Option<CompressedProof>,
+    output_tree_index: u8,
+    light_cpi_accounts: &InvokeCpiAccounts,
+) -> ProgramResult {
+    let (inputs, outputs, read_only_addresses) = match check.entry {
+        DenylistEntryProof::Absent {
+            address_tree_index,
+            address_root_index,
+        } => {
+            check_address_tree(&check.denylist, light_cpi_accounts, address_tree_index)?;
+            let read_only_address = PackedReadOnlyAddress {
+                address: derive_denylist_entry_address(&check.denylist, claimant),
+                address_merkle_tree_root_index: address_root_index,
+                address_merkle_tree_account_index: address_tree_index,
+            };
+            (Vec::new(), Vec::new(), vec![read_only_address])
+        }
+        // the removed entry is written again unchanged.
+        DenylistEntryProof::Existing {
+            merkle_context,
+            root_index,
+        } => (
+            vec![entry_input(
+                &check.denylist,
+                claimant,
+                false,
+                merkle_context,
+                root_index,
+            )],
+            vec![entry_output(
+                &check.denylist,
+                claimant,
+                false,
+                output_tree_index,
+            )],
+            Vec::new(),
+        ),
+    };
+    light_system::invoke_cpi(
+        proof,
+        Vec::new(),
+        inputs,
+        outputs,
+        read_only_addresses,
+        light_cpi_accounts,
+    )
+}
+
+/// Parses the accounts of `AddToDenylist` and `RemoveFromDenylist`, the
+/// denylist authority pays for the Light system CPI.
+fn denylist_cpi_accounts<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<InvokeCpiAccounts<'a, 'info>, ProgramError> {
+    if accounts.len() < 9 {
+        msg!("Expected at least 9 accounts, got {}", accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (accounts, tree_accounts) = accounts.split_at(9);
+    let authority_info = &accounts[0];
+    // CHECK:
+    if !authority_info.is_signer {
+        msg!("Denylist authority must be a signer");
+        authority_info.key.log();
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(InvokeCpiAccounts {
+        fee_payer: authority_info.clone(),
+        cpi_authority_pda: accounts[1].clone(),
+        light_system_program: accounts[2].clone(),
+        registered_program_pda: accounts[3].clone(),
+        noop_program: accounts[4].clone(),
+        account_compression_authority: accounts[5].clone(),
+        account_compression_program: accounts[6].clone(),
+        invoking_program: accounts[7].clone(),
+        system_program: accounts[8].clone(),
+        tree_accounts,
+        cpi_context_index: None,
+    })
+}
+
+/// Checks that the tree account at `address_tree_index` is the address tree
+/// of `denylist`, entries in other trees don't belong to the denylist.
+fn check_address_tree(
+    denylist: &Denylist,
+    light_cpi_accounts: &InvokeCpiAccounts,
+    address_tree_index: u8,
+) -> ProgramResult {
+    let address_tree = light_cpi_accounts
+        .tree_accounts
+        .get(address_tree_index as usize)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    // CHECK:
+    if *address_tree.key != denylist.address_tree {
+        msg!(
+            "Invalid denylist address tree provided. Expected: {}. Found: {}.",
+            denylist.address_tree,
+            address_tree.key
+        );
+        return Err(ClaimError::InvalidDenylistEntry.into());
+    }
+    Ok(())
+}
+
+/// The existing entry of `address` in `denylist`. The Light system program
+/// verifies its hash in the validity proof, so an entry in another state
+/// than `denied` fails.
+fn entry_input(
+    denylist: &Denylist,
+    address: &Pubkey,
+    denied: bool,
+    merkle_context: PackedMerkleContext,
+    root_index: u16,
+) -> InAccount {
+    let entry = DenylistEntry {
+        authority: denylist.authority,
+        address: *address,
+        denied,
+    };
+    InAccount {
+        discriminator: DenylistEntry::DISCRIMINATOR,
+        data_hash: entry.data_hash(),
+        merkle_context,
+        root_index,
+        lamports: 0,
+        address: Some(derive_denylist_entry_address(denylist, address)),
+    }
+}
+
+/// The entry of `address` in `denylist`, written to `output_tree_index`.
+fn entry_output(
+    denylist: &Denylist,
+    address: &Pubkey,
+    denied: bool,
+    output_tree_index: u8,
+) -> OutputCompressedAccountWithPackedContext {
+    let entry = DenylistEntry {
+        authority: denylist.authority,
+        address: *address,
+        denied,
+    };
+    OutputCompressedAccountWithPackedContext {
+        compressed_account: CompressedAccount {
+            owner: crate::id().to_bytes().into(),
+            lamports: 0,
+            address: Some(derive_denylist_entry_address(denylist, address)),
+            data: Some(CompressedAccountData {
+                discriminator: DenylistEntry::DISCRIMINATOR,
+                data: borsh::to_vec(&entry).unwrap(),
+                data_hash: entry.data_hash(),
+            }),
+        },
+        merkle_tree_index: output_tree_index,
+    }
+}
@@ -0,0 +1,243 @@
+//! Revocable vesting grants.
+//!
+//! A grant PDA holds the unclaimed tokens of a grant. The employee claims
+//! vested tokens from it, the change stays in the grant PDA. Revoking a grant
+//! returns the tokens unvested at the revoke slot to the grantor and moves the
+//! vested remainder to airdrop PDAs of the employee, where it is claimed with
+//! a regular claim. Tokens already vested unlock at the start slot of the
+//! grant, the ones vesting until a future revoke slot unlock at the revoke
+//! slot, so clients derive both airdrop PDAs from the grant and revoke slot.
+use super::{check_claim_pda, check_not_paused, ClaimAccountInfos};
+use crate::{
+    ctoken,
+    error::ClaimError,
+    pda::{AirdropSeeds, GRANT_SEED},
+    state::Grant,
+};
+use light_compressed_account::{
+    compressed_account::PackedMerkleContext, instruction_data::compressed_proof::CompressedProof,
+};
+use light_compressed_token_sdk::{
+    cpi::account_info::get_compressed_token_account_info, state::PackedTokenTransferOutputData,
+};
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg, pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+pub fn process_create_grant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    grant: Grant,
+    bump_seed: u8,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let accounts = ClaimAccountInfos::try_from_slice(accounts, None, output_tree_index)?;
+    check_signer_is(accounts.claimant, &grant.grantor)?;
+    // CHECK:
+    if grant.end_slot < grant.start_slot {
+        msg!(
+            "Grant end slot ({}) is before its start slot ({}).",
+            grant.end_slot,
+            grant.start_slot
+        );
+        return Err(ClaimError::InvalidGrant.into());
+    }
+    // CHECK:
+    let commitment = grant.commitment();
+    check_claim_pda(
+        &[GRANT_SEED, &commitment, &[bump_seed]],
+        program_id,
+        accounts.associated_airdrop_pda.key,
+    )?;
+
+    let mut light_cpi_accounts = accounts.light_cpi_accounts();
+    light_cpi_accounts.authority = accounts.claimant.clone();
+    ctoken::compress(
+        &grant.mint,
+        vec![PackedTokenTransferOutputData {
+            owner: *accounts.associated_airdrop_pda.key,
+            amount: grant.amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        }],
+        &light_cpi_accounts,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_claim_grant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    grant: Grant,
+    bump_seed: u8,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let accounts =
+        ClaimAccountInfos::try_from_slice(accounts, Some(&merkle_context), output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+    check_signer_is(accounts.claimant, &grant.employee)?;
+
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    let claimable = grant.claimable_amount(amount, current_slot);
+    if claimable == 0 {
+        msg!("No vested tokens to claim at slot {}.", current_slot);
+        return Err(ClaimError::TokensLocked.into());
+    }
+
+    // CHECK:
+    let commitment = grant.commitment();
+    let bump = [bump_seed];
+    let signer_seeds: &[&[u8]] = &[GRANT_SEED, &commitment, &bump];
+    check_claim_pda(
+        signer_seeds,
+        program_id,
+        accounts.associated_airdrop_pda.key,
+    )?;
+
+    let mut outputs = Vec::new();
+    let unvested = amount - claimable;
+    if unvested > 0 {
+        outputs.push(PackedTokenTransferOutputData {
+            owner: *accounts.associated_airdrop_pda.key,
+            amount: unvested,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        });
+    }
+    ctoken::transfer_and_decompress(
+        &grant.mint,
+        vec![get_compressed_token_account_info(
+            merkle_context,
+            root_index,
+            amount,
+            lamports,
+        )],
+        outputs,
+        proof,
+        &accounts.light_cpi_accounts(),
+        &[signer_seeds],
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_revoke_grant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    grant: Grant,
+    bump_seed: u8,
+    revoke_slot: u64,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let accounts =
+        ClaimAccountInfos::try_from_slice(accounts, Some(&merkle_context), output_tree_index)?;
+    check_signer_is(accounts.claimant, &grant.grantor)?;
+
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if revoke_slot < current_slot {
+        msg!(
+            "Revoke slot ({}) is before the current slot ({}).",
+            revoke_slot,
+            current_slot
+        );
+        return Err(ClaimError::InvalidRevokeSlot.into());
+    }
+    // CHECK:
+    if revoke_slot > grant.end_slot {
+        msg!(
+            "Revoke slot ({}) is after the end slot ({}).",
+            revoke_slot,
+            grant.end_slot
+        );
+        return Err(ClaimError::InvalidRevokeSlot.into());
+    }
+
+    // CHECK:
+    let commitment = grant.commitment();
+    let bump = [bump_seed];
+    let signer_seeds: &[&[u8]] = &[GRANT_SEED, &commitment, &bump];
+    check_claim_pda(
+        signer_seeds,
+        program_id,
+        accounts.associated_airdrop_pda.key,
+    )?;
+
+    let unvested = grant.amount - grant.vested_amount(revoke_slot);
+    let returned = unvested.min(amount);
+    let vested = amount - returned;
+    // claimed tokens were vested by the current slot.
+    let claimed = grant.amount.saturating_sub(amount);
+    let unlocked = grant
+        .vested_amount(current_slot)
+        .saturating_sub(claimed)
+        .min(vested);
+    let locked = vested - unlocked;
+    msg!(
+        "Revoking grant at slot {}: {} vested, {} of them locked, {} returned.",
+        revoke_slot,
+        vested,
+        locked,
+        returned
+    );
+
+    let mut outputs = Vec::new();
+    if returned > 0 {
+        outputs.push(PackedTokenTransferOutputData {
+            owner: grant.grantor,
+            amount: returned,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        });
+    }
+    for (amount, unlock_slot) in [(unlocked, grant.start_slot), (locked, revoke_slot)] {
+        if amount == 0 {
+            continue;
+        }
+        let (employee_airdrop_pda, _) =
+            AirdropSeeds::new(&grant.employee, &grant.mint, unlock_slot).find_program_address();
+        outputs.push(PackedTokenTransferOutputData {
+            owner: employee_airdrop_pda,
+            amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        });
+    }
+    ctoken::transfer_and_decompress(
+        &grant.mint,
+        vec![get_compressed_token_account_info(
+            merkle_context,
+            root_index,
+            amount,
+            lamports,
+        )],
+        outputs,
+        proof,
+        &accounts.light_cpi_accounts(),
+        &[signer_seeds],
+    )
+}
+
+fn check_signer_is(signer: &AccountInfo, expected: &Pubkey) -> ProgramResult {
+    // CHECK:
+    if signer.key != expected {
+        msg!("Expected signer {}. Found: {}.", expected, signer.key);
+        return Err(ClaimError::Unauthorized.into());
+    }
+    Ok(())
+}
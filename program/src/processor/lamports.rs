@@ -0,0 +1,117 @@
+//! Compressed SOL airdrops.
+//!
+//! A lamports airdrop PDA is derived from (claimant, unlock_slot) in its own
+//! seed domain and owns a compressed SOL account. Claiming decompresses its
+//! lamports to the decompress destination through the Light system program.
+use super::{check_claim_pda, check_not_paused};
+use crate::{
+    error::ClaimError,
+    light_system::{self, LightSystemCpiAccounts, LIGHT_SYSTEM_PROGRAM_ID},
+    pda::LAMPORTS_SEED,
+};
+use light_compressed_account::{
+    compressed_account::{PackedCompressedAccountWithMerkleContext, PackedMerkleContext},
+    instruction_data::compressed_proof::CompressedProof,
+};
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+/// Number of accounts before the tree accounts.
+const LEN: usize = 12;
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_claim_lamports(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    lamports: u64,
+    unlock_slot: u64,
+    bump_seed: u8,
+) -> ProgramResult {
+    let num_tree_accounts = merkle_context
+        .merkle_tree_pubkey_index
+        .max(merkle_context.queue_pubkey_index) as usize
+        + 1;
+    if accounts.len() != LEN + num_tree_accounts {
+        msg!(
+            "Expected {} accounts, got {}",
+            LEN + num_tree_accounts,
+            accounts.len()
+        );
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (accounts, tree_accounts) = accounts.split_at(LEN);
+    let [claimant, fee_payer, associated_airdrop_pda, light_system_program, registered_program_pda, noop_program, account_compression_authority, account_compression_program, sol_pool_pda, decompress_destination, system_program, config] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // CHECK:
+    if !claimant.is_signer {
+        msg!("Claimant must be a signer");
+        claimant.key.log();
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // CHECK:
+    if !fee_payer.is_signer {
+        msg!("Fee payer must be a signer");
+        fee_payer.key.log();
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // CHECK:
+    if light_system_program.key != &LIGHT_SYSTEM_PROGRAM_ID {
+        msg!("Invalid light system program.");
+        light_system_program.key.log();
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_not_paused(program_id, config)?;
+
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    if current_slot < unlock_slot {
+        msg!(
+            "Tokens are still locked: current slot ({}) is less than unlock slot ({}).",
+            current_slot,
+            unlock_slot
+        );
+        return Err(ClaimError::TokensLocked.into());
+    }
+
+    // CHECK:
+    let unlock_slot = unlock_slot.to_le_bytes();
+    let bump = [bump_seed];
+    let signer_seeds: &[&[u8]] = &[LAMPORTS_SEED, claimant.key.as_ref(), &unlock_slot, &bump];
+    check_claim_pda(signer_seeds, program_id, associated_airdrop_pda.key)?;
+
+    light_system::decompress_lamports(
+        PackedCompressedAccountWithMerkleContext {
+            compressed_account: light_system::compressed_sol_account(
+                associated_airdrop_pda.key,
+                lamports,
+            ),
+            merkle_context,
+            root_index,
+            read_only: false,
+        },
+        proof,
+        &LightSystemCpiAccounts {
+            fee_payer: fee_payer.clone(),
+            authority: associated_airdrop_pda.clone(),
+            light_system_program: light_system_program.clone(),
+            registered_program_pda: registered_program_pda.clone(),
+            noop_program: noop_program.clone(),
+            account_compression_authority: account_compression_authority.clone(),
+            account_compression_program: account_compression_program.clone(),
+            sol_pool_pda: sol_pool_pda.clone(),
+            decompress_destination: decompress_destination.clone(),
+            system_program: system_program.clone(),
+            tree_accounts,
+        },
+        &[signer_seeds],
+    )
+}
@@ -0,0 +1,354 @@
+//! Token migrations from an old mint to a new mint.
+//!
+//! A migration PDA is derived from the migration authority and the old and
+//! new mint, so each authority has its own migration of a pair of mints. It
+//! stores the migration ratio and progress and owns the compressed new tokens
+//! to release.
+//! `Migrate` escrows old tokens of the claimant with the migration PDA, SPL
+//! tokens are compressed to it, and decompresses the new tokens they migrate
+//! to from its balance to the decompress destination. Old tokens are escrowed,
+//! not burned, and stay with the migration PDA as compressed old tokens until
+//! the migration authority withdraws them, or unreleased new tokens, with
+//! `WithdrawMigration`.
+use super::{check_not_paused, create_pda_account, ClaimAccountInfos};
+use crate::{
+    ctoken::{self, CtokenCpiAccounts},
+    error::ClaimError,
+    instruction::{MigrationInput, MigrationSource},
+    pda::MIGRATION_SEED,
+    state::Migration,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use light_compressed_token_sdk::{
+    cpi::account_info::get_compressed_token_account_info, state::PackedTokenTransferOutputData,
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+pub fn process_create_migration(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    old_mint: Pubkey,
+    new_mint: Pubkey,
+    ratio_numerator: u64,
+    ratio_denominator: u64,
+    bump_seed: u8,
+) -> ProgramResult {
+    let [authority_info, migration_info, system_program_info] = accounts else {
+        msg!("Expected 3 accounts, got {}", accounts.len());
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    // CHECK:
+    if !authority_info.is_signer {
+        msg!("Migration authority must be a signer");
+        authority_info.key.log();
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // CHECK:
+    if ratio_numerator == 0 || ratio_denominator == 0 || old_mint == new_mint {
+        msg!(
+            "Invalid migration of {} to {} at {}/{}.",
+            old_mint,
+            new_mint,
+            ratio_numerator,
+            ratio_denominator
+        );
+        return Err(ClaimError::InvalidMigration.into());
+    }
+    // CHECK:
+    let bump = [bump_seed];
+    let seeds: &[&[u8]] = &[
+        MIGRATION_SEED,
+        authority_info.key.as_ref(),
+        old_mint.as_ref(),
+        new_mint.as_ref(),
+        &bump,
+    ];
+    check_migration_pda(seeds, program_id, migration_info.key)?;
+
+    // Fails if the migration already exists.
+    create_pda_account(
+        program_id,
+        authority_info,
+        migration_info,
+        system_program_info,
+        Migration::LEN,
+        seeds,
+    )?;
+
+    msg!(
+        "Migrating {} to {} at {}/{}.",
+        old_mint,
+        new_mint,
+        ratio_numerator,
+        ratio_denominator
+    );
+    let migration = Migration {
+        authority: *authority_info.key,
+        old_mint,
+        new_mint,
+        ratio_numerator,
+        ratio_denominator,
+        migrated_amount: 0,
+        released_amount: 0,
+        bump_seed,
+    };
+    migration.serialize(&mut &mut migration_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+pub fn process_migrate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    source: MigrationSource,
+    vault: MigrationInput,
+    output_tree_index: u8,
+) -> ProgramResult {
+    // the tree accounts reach up to the highest index of either input.
+    let tree_context = std::iter::once(&vault.merkle_context)
+        .chain(match &source {
+            MigrationSource::Spl => None,
+            MigrationSource::Compressed(input) => Some(&input.merkle_context),
+        })
+        .max_by_key(|context| {
+            context
+                .merkle_tree_pubkey_index
+                .max(context.queue_pubkey_index)
+        });
+    let (accounts, remaining_accounts) =
+        ClaimAccountInfos::split(accounts, tree_context, output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+
+    let migration_info = accounts.associated_airdrop_pda;
+    let mut migration = load_migration(program_id, migration_info)?;
+
+    let new_amount = migration
+        .new_amount(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    // CHECK:
+    if new_amount == 0 {
+        msg!("Migrating {} old tokens releases no new tokens.", amount);
+        return Err(ClaimError::InvalidClaimAmount.into());
+    }
+    // CHECK:
+    if new_amount > vault.amount {
+        msg!(
+            "Migration vault holds {} new tokens, {} required.",
+            vault.amount,
+            new_amount
+        );
+        return Err(ClaimError::InsufficientMigrationVault.into());
+    }
+    msg!(
+        "Migrating {} old tokens to {} new tokens.",
+        amount,
+        new_amount
+    );
+
+    let escrow = PackedTokenTransferOutputData {
+        owner: *migration_info.key,
+        amount,
+        lamports: None,
+        merkle_tree_index: accounts.output_tree_index,
+        tlv: None,
+    };
+    match (source, remaining_accounts) {
+        (MigrationSource::Spl, [old_token_pool_pda, old_token_account]) => ctoken::compress(
+            &migration.old_mint,
+            vec![escrow],
+            &CtokenCpiAccounts {
+                authority: accounts.claimant.clone(),
+                token_pool_pda: old_token_pool_pda.clone(),
+                decompress_destination: old_token_account.clone(),
+                ..accounts.light_cpi_accounts()
+            },
+        )?,
+        (MigrationSource::Compressed(input), []) => {
+            // CHECK:
+            let Some(change) = input.amount.checked_sub(amount) else {
+                msg!(
+                    "Cannot migrate {} old tokens from an account of {}.",
+                    amount,
+                    input.amount
+                );
+                return Err(ClaimError::InvalidClaimAmount.into());
+            };
+            let mut outputs = vec![escrow];
+            if change > 0 {
+                outputs.push(PackedTokenTransferOutputData {
+                    owner: *accounts.claimant.key,
+                    amount: change,
+                    lamports: None,
+                    merkle_tree_index: accounts.output_tree_index,
+                    tlv: None,
+                });
+            }
+            ctoken::transfer_and_decompress(
+                &migration.old_mint,
+                vec![get_compressed_token_account_info(
+                    input.merkle_context,
+                    input.root_index,
+                    input.amount,
+                    input.lamports,
+                )],
+                outputs,
+                input.proof,
+                &CtokenCpiAccounts {
+                    authority: accounts.claimant.clone(),
+                    ..accounts.light_cpi_accounts()
+                },
+                &[],
+            )?
+        }
+        (_, remaining_accounts) => {
+            msg!(
+                "Unexpected {} accounts after the tree accounts",
+                remaining_accounts.len()
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    // Releases `new_amount` from the vault, the change stays with the
+    // migration PDA.
+    let mut outputs = Vec::new();
+    if new_amount < vault.amount {
+        outputs.push(PackedTokenTransferOutputData {
+            owner: *migration_info.key,
+            amount: vault.amount - new_amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        });
+    }
+    let bump = [migration.bump_seed];
+    let signer_seeds: &[&[u8]] = &[
+        MIGRATION_SEED,
+        migration.authority.as_ref(),
+        migration.old_mint.as_ref(),
+        migration.new_mint.as_ref(),
+        &bump,
+    ];
+    ctoken::transfer_and_decompress(
+        &migration.new_mint,
+        vec![get_compressed_token_account_info(
+            vault.merkle_context,
+            vault.root_index,
+            vault.amount,
+            vault.lamports,
+        )],
+        outputs,
+        vault.proof,
+        &accounts.light_cpi_accounts(),
+        &[signer_seeds],
+    )?;
+
+    // Updated after the CPIs, which receive the migration PDA read-only.
+    migration.migrated_amount = migration
+        .migrated_amount
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    migration.released_amount = migration
+        .released_amount
+        .checked_add(new_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    migration.serialize(&mut &mut migration_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+pub fn process_withdraw_migration(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    input: MigrationInput,
+    output_tree_index: u8,
+) -> ProgramResult {
+    let accounts = ClaimAccountInfos::try_from_slice(
+        accounts,
+        Some(&input.merkle_context),
+        output_tree_index,
+    )?;
+    let migration_info = accounts.associated_airdrop_pda;
+    let migration = load_migration(program_id, migration_info)?;
+    // CHECK:
+    if *accounts.claimant.key != migration.authority {
+        msg!(
+            "Expected migration authority {}. Found: {}.",
+            migration.authority,
+            accounts.claimant.key
+        );
+        return Err(ClaimError::Unauthorized.into());
+    }
+    // CHECK:
+    if mint != migration.old_mint && mint != migration.new_mint {
+        msg!("Migration holds no tokens of mint {}.", mint);
+        return Err(ClaimError::InvalidMigration.into());
+    }
+    msg!("Withdrawing {} tokens of mint {}.", input.amount, mint);
+
+    let bump = [migration.bump_seed];
+    let signer_seeds: &[&[u8]] = &[
+        MIGRATION_SEED,
+        migration.authority.as_ref(),
+        migration.old_mint.as_ref(),
+        migration.new_mint.as_ref(),
+        &bump,
+    ];
+    ctoken::transfer_and_decompress(
+        &mint,
+        vec![get_compressed_token_account_info(
+            input.merkle_context,
+            input.root_index,
+            input.amount,
+            input.lamports,
+        )],
+        vec![],
+        input.proof,
+        &accounts.light_cpi_accounts(),
+        &[signer_seeds],
+    )
+}
+
+fn load_migration(
+    program_id: &Pubkey,
+    migration_info: &AccountInfo,
+) -> Result<Migration, ProgramError> {
+    // CHECK:
+    if migration_info.owner != program_id {
+        msg!("Migration account is not owned by the claim program.");
+        migration_info.key.log();
+        return Err(ClaimError::InvalidMigration.into());
+    }
+    let migration = Migration::deserialize(&mut &migration_info.try_borrow_data()?[..])
+        .map_err(|_| ClaimError::InvalidMigration)?;
+    // CHECK:
+    check_migration_pda(
+        &[
+            MIGRATION_SEED,
+            migration.authority.as_ref(),
+            migration.old_mint.as_ref(),
+            migration.new_mint.as_ref(),
+            &[migration.bump_seed],
+        ],
+        program_id,
+        migration_info.key,
+    )?;
+    Ok(migration)
+}
+
+fn check_migration_pda(seeds: &[&[u8]], program_id: &Pubkey, migration: &Pubkey) -> ProgramResult {
+    let derived_pda = Pubkey::create_program_address(seeds, program_id)
+        .map_err(|_| ClaimError::InvalidMigration)?;
+    if derived_pda != *migration {
+        msg!(
+            "Invalid migration account provided. Expected: {}. Found: {}.",
+            derived_pda,
+            migration
+        );
+        return Err(ClaimError::InvalidMigration.into());
+    }
+    Ok(())
+}
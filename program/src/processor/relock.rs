@@ -0,0 +1,337 @@
+//! Extending the lock of airdrops.
+//!
+//! `Relock` moves an airdrop to the airdrop PDA of the same claimant, mint and
+//! campaign terms with a later unlock slot. A campaign can reward the added
+//! lock duration from a bonus vault, a PDA of the claim program that commits
+//! to the funder, the mint and the `RelockBonus` rate and holds compressed
+//! tokens of the mint. The funder signs every relock paid from its vault, so
+//! only airdrops of its campaign draw the bonus. The bonus is locked with the
+//! relocked airdrop.
+//!
+//! `Merge` moves several airdrops of a claimant and mint into the airdrop PDA
+//! with an unlock slot after all of theirs, so merging never shortens a lock.
+use super::{check_claim_pda, check_not_paused, ClaimAccountInfos};
+use crate::{
+    ctoken::{self, CtokenCpiAccounts},
+    error::ClaimError,
+    instruction::{MergePosition, RelockBonusInput},
+    pda::{AirdropSeeds, BONUS_VAULT_SEED},
+    state::ClaimFee,
+};
+use light_compressed_account::{
+    compressed_account::PackedMerkleContext, instruction_data::compressed_proof::CompressedProof,
+};
+use light_compressed_token_sdk::{
+    cpi::account_info::get_compressed_token_account_info,
+    state::{InputTokenDataWithContext, PackedTokenTransferOutputData},
+};
+use solana_program::{
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_relock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: Option<CompressedProof>,
+    root_index: u16,
+    merkle_context: PackedMerkleContext,
+    amount: u64,
+    lamports: Option<u64>,
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    new_unlock_slot: u64,
+    bonus: Option<RelockBonusInput>,
+    output_tree_index: u8,
+) -> ProgramResult {
+    // the tree accounts reach up to the highest index of either input.
+    let tree_context = std::iter::once(&merkle_context)
+        .chain(bonus.as_ref().map(|bonus| &bonus.merkle_context))
+        .max_by_key(|context| {
+            context
+                .merkle_tree_pubkey_index
+                .max(context.queue_pubkey_index)
+        });
+    let (accounts, remaining_accounts) =
+        ClaimAccountInfos::split(accounts, tree_context, output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+
+    // CHECK:
+    let current_slot = Clock::get()?.slot;
+    let locked_until = unlock_slot.max(current_slot);
+    if new_unlock_slot <= locked_until {
+        msg!(
+            "New unlock slot ({}) must be after slot {}.",
+            new_unlock_slot,
+            locked_until
+        );
+        return Err(ClaimError::InvalidRelockSlot.into());
+    }
+
+    // CHECK:
+    let seeds = AirdropSeeds::new(accounts.claimant.key, &mint, unlock_slot).with_fee(fee.as_ref());
+    let bump = [bump_seed];
+    let mut signer_seeds = seeds.seeds();
+    signer_seeds.push(&bump);
+    check_claim_pda(
+        &signer_seeds,
+        program_id,
+        accounts.associated_airdrop_pda.key,
+    )?;
+
+    let (new_airdrop_pda, _) = AirdropSeeds::new(accounts.claimant.key, &mint, new_unlock_slot)
+        .with_fee(fee.as_ref())
+        .find_program_address();
+    msg!("Relocking airdrop until slot {}.", new_unlock_slot);
+
+    move_airdrop(
+        &accounts,
+        accounts.associated_airdrop_pda,
+        &signer_seeds,
+        &mint,
+        get_compressed_token_account_info(merkle_context, root_index, amount, lamports),
+        proof,
+        new_airdrop_pda,
+    )?;
+
+    match (bonus, remaining_accounts) {
+        (None, []) => Ok(()),
+        (Some(bonus), [bonus_vault, funder]) => pay_bonus(
+            program_id,
+            &accounts,
+            bonus_vault,
+            funder,
+            bonus,
+            &mint,
+            new_airdrop_pda,
+            amount,
+            new_unlock_slot - locked_until,
+        ),
+        (_, remaining_accounts) => {
+            msg!(
+                "Unexpected {} accounts after the tree accounts",
+                remaining_accounts.len()
+            );
+            Err(ProgramError::InvalidArgument)
+        }
+    }
+}
+
+/// Merges the airdrops of `positions` into the airdrop PDA unlocking at
+/// `unlock_slot`. The airdrop PDAs of the positions follow the tree accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn process_merge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    unlock_slot: u64,
+    bump_seed: u8,
+    fee: Option<ClaimFee>,
+    positions: Vec<MergePosition>,
+    output_tree_index: u8,
+) -> ProgramResult {
+    // the tree accounts reach up to the highest index of any input.
+    let tree_context = positions
+        .iter()
+        .map(|position| &position.merkle_context)
+        .max_by_key(|context| {
+            context
+                .merkle_tree_pubkey_index
+                .max(context.queue_pubkey_index)
+        });
+    // CHECK:
+    if tree_context.is_none() {
+        msg!("Merge has no positions.");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (accounts, remaining_accounts) =
+        ClaimAccountInfos::split(accounts, tree_context, output_tree_index)?;
+    check_not_paused(program_id, accounts.config)?;
+
+    // CHECK:
+    if remaining_accounts.len() != positions.len() {
+        msg!(
+            "Expected {} accounts after the tree accounts, got {}",
+            positions.len(),
+            remaining_accounts.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // CHECK:
+    let merged_seeds =
+        AirdropSeeds::new(accounts.claimant.key, &mint, unlock_slot).with_fee(fee.as_ref());
+    let bump = [bump_seed];
+    let mut seeds = merged_seeds.seeds();
+    seeds.push(&bump);
+    check_claim_pda(&seeds, program_id, accounts.associated_airdrop_pda.key)?;
+
+    for (position, associated_airdrop_pda) in positions.into_iter().zip(remaining_accounts) {
+        // CHECK:
+        if position.unlock_slot >= unlock_slot {
+            msg!(
+                "Airdrop unlocking at slot {} can't merge into slot {}.",
+                position.unlock_slot,
+                unlock_slot
+            );
+            return Err(ClaimError::InvalidRelockSlot.into());
+        }
+
+        // CHECK:
+        let seeds = AirdropSeeds::new(accounts.claimant.key, &mint, position.unlock_slot)
+            .with_fee(fee.as_ref());
+        let bump = [position.bump_seed];
+        let mut signer_seeds = seeds.seeds();
+        signer_seeds.push(&bump);
+        check_claim_pda(&signer_seeds, program_id, associated_airdrop_pda.key)?;
+
+        msg!(
+            "Merging airdrop unlocking at slot {} into slot {}.",
+            position.unlock_slot,
+            unlock_slot
+        );
+        move_airdrop(
+            &accounts,
+            associated_airdrop_pda,
+            &signer_seeds,
+            &mint,
+            get_compressed_token_account_info(
+                position.merkle_context,
+                position.root_index,
+                position.amount,
+                position.lamports,
+            ),
+            position.proof,
+            *accounts.associated_airdrop_pda.key,
+        )?;
+    }
+    Ok(())
+}
+
+/// Moves the compressed tokens of the airdrop PDA `authority` to
+/// `new_airdrop_pda`.
+fn move_airdrop<'info>(
+    accounts: &ClaimAccountInfos<'_, 'info>,
+    authority: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    mint: &Pubkey,
+    input: InputTokenDataWithContext,
+    proof: Option<CompressedProof>,
+    new_airdrop_pda: Pubkey,
+) -> ProgramResult {
+    let amount = input.amount;
+    ctoken::transfer_and_decompress(
+        mint,
+        vec![input],
+        vec![PackedTokenTransferOutputData {
+            owner: new_airdrop_pda,
+            amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        }],
+        proof,
+        &CtokenCpiAccounts {
+            authority: authority.clone(),
+            ..accounts.light_cpi_accounts()
+        },
+        &[signer_seeds],
+    )
+}
+
+/// Transfers the bonus for locking `amount` tokens `added_slots` longer from
+/// the bonus vault to the new airdrop PDA, at most the vault balance. The
+/// change stays in the bonus vault.
+#[allow(clippy::too_many_arguments)]
+fn pay_bonus<'info>(
+    program_id: &Pubkey,
+    accounts: &ClaimAccountInfos<'_, 'info>,
+    bonus_vault: &AccountInfo<'info>,
+    funder: &AccountInfo<'info>,
+    bonus: RelockBonusInput,
+    mint: &Pubkey,
+    new_airdrop_pda: Pubkey,
+    amount: u64,
+    added_slots: u64,
+) -> ProgramResult {
+    // CHECK:
+    if *funder.key != bonus.bonus.funder {
+        msg!(
+            "Invalid bonus funder provided. Expected: {}. Found: {}.",
+            bonus.bonus.funder,
+            funder.key
+        );
+        return Err(ClaimError::InvalidBonusVault.into());
+    }
+    // CHECK:
+    if !funder.is_signer {
+        msg!("Bonus funder must sign the relock.");
+        return Err(ClaimError::MissingRequiredSignature.into());
+    }
+
+    // CHECK:
+    let commitment = bonus.bonus.commitment();
+    let bump = [bonus.bump_seed];
+    let signer_seeds: &[&[u8]] = &[
+        BONUS_VAULT_SEED,
+        funder.key.as_ref(),
+        mint.as_ref(),
+        &commitment,
+        &bump,
+    ];
+    let derived_pda = Pubkey::create_program_address(signer_seeds, program_id)
+        .map_err(|_| ClaimError::InvalidBonusVault)?;
+    if derived_pda != *bonus_vault.key {
+        msg!(
+            "Invalid bonus vault provided. Expected: {}. Found: {}.",
+            derived_pda,
+            bonus_vault.key
+        );
+        return Err(ClaimError::InvalidBonusVault.into());
+    }
+
+    let bonus_amount = bonus
+        .bonus
+        .bonus_amount(amount, added_slots)
+        .min(bonus.amount);
+    if bonus_amount == 0 {
+        return Ok(());
+    }
+    msg!("Relock bonus of {} tokens.", bonus_amount);
+
+    let mut outputs = vec![PackedTokenTransferOutputData {
+        owner: new_airdrop_pda,
+        amount: bonus_amount,
+        lamports: None,
+        merkle_tree_index: accounts.output_tree_index,
+        tlv: None,
+    }];
+    if bonus_amount < bonus.amount {
+        outputs.push(PackedTokenTransferOutputData {
+            owner: *bonus_vault.key,
+            amount: bonus.amount - bonus_amount,
+            lamports: None,
+            merkle_tree_index: accounts.output_tree_index,
+            tlv: None,
+        });
+    }
+    ctoken::transfer_and_decompress(
+        mint,
+        vec![get_compressed_token_account_info(
+            bonus.merkle_context,
+            bonus.root_index,
+            bonus.amount,
+            bonus.lamports,
+        )],
+        outputs,
+        bonus.proof,
+        &CtokenCpiAccounts {
+            authority: bonus_vault.clone(),
+            ..accounts.light_cpi_accounts()
+        },
+        &[signer_seeds],
+    )
+}
@@ -0,0 +1,739 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use light_compressed_account::compressed_account::PackedMerkleContext;
+use solana_program::{hash::hashv, pubkey::Pubkey};
+
+/// Program wide configuration, stored in the config PDA.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ClaimConfig {
+    /// Can pause claims and hand over the admin role.
+    pub admin: Pubkey,
+    /// Admin proposed by `TransferAdmin`, becomes admin once it accepts.
+    pub pending_admin: Option<Pubkey>,
+    /// Claims are rejected while paused.
+    pub paused: bool,
+    pub bump_seed: u8,
+}
+
+impl ClaimConfig {
+    pub const LEN: usize = 32 + (1 + 32) + 1 + 1;
+}
+
+/// Denylist of `authority`, committed in the airdrop PDA seeds of campaigns
+/// that check it. Its entries are compressed accounts of the claim program
+/// with addresses in `address_tree`, a batched address tree.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Denylist {
+    pub authority: Pubkey,
+    pub address_tree: Pubkey,
+}
+
+impl Denylist {
+    /// Airdrop PDA seed committing to the denylist.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"denylist", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+}
+
+/// Compressed account data of the entry of `address` in the denylist of
+/// `authority`. Addresses can't be removed from an address tree, so a removed
+/// entry stays with `denied` unset and is denied again by setting it.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct DenylistEntry {
+    pub authority: Pubkey,
+    pub address: Pubkey,
+    pub denied: bool,
+}
+
+impl DenylistEntry {
+    pub const DISCRIMINATOR: [u8; 8] = *b"denylist";
+
+    /// Hash of the account data, truncated to the bn254 field size.
+    pub fn data_hash(&self) -> [u8; 32] {
+        let mut data_hash = hashv(&[&borsh::to_vec(self).unwrap()]).to_bytes();
+        data_hash[0] = 0;
+        data_hash
+    }
+}
+
+/// State of a denylist entry, proven by the validity proof of the instruction.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DenylistEntryProof {
+    /// The entry doesn't exist, proven by non-inclusion of its address in
+    /// the address tree at `address_tree_index` in the tree accounts.
+    Absent {
+        address_tree_index: u8,
+        address_root_index: u16,
+    },
+    /// The entry exists, proven by inclusion as it is spent and written again.
+    Existing {
+        merkle_context: PackedMerkleContext,
+        root_index: u16,
+    },
+}
+
+/// Denylist checked by a claim of a campaign that commits to `denylist`.
+///
+/// The claimant passes if its entry is absent or removed. The validity proof
+/// of the claim covers the token input and the entry, the compressed token
+/// transfer is stored in the CPI context account at `cpi_context_index` in
+/// the tree accounts and executed with the entry proof.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DenylistCheck {
+    pub denylist: Denylist,
+    pub entry: DenylistEntryProof,
+    pub cpi_context_index: u8,
+}
+
+/// Migration from `old_mint` to `new_mint`, stored in the migration PDA.
+///
+/// Claimants escrow old tokens with the migration PDA and receive new tokens
+/// from its compressed balance of `new_mint` at the fixed ratio
+/// `ratio_numerator / ratio_denominator`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub authority: Pubkey,
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub ratio_numerator: u64,
+    pub ratio_denominator: u64,
+    /// Old tokens escrowed so far.
+    pub migrated_amount: u64,
+    /// New tokens released so far.
+    pub released_amount: u64,
+    pub bump_seed: u8,
+}
+
+impl Migration {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// New tokens released for `old_amount` old tokens, rounded down. `None`
+    /// if the amount doesn't fit in a u64.
+    pub fn new_amount(&self, old_amount: u64) -> Option<u64> {
+        (old_amount as u128 * self.ratio_numerator as u128)
+            .checked_div(self.ratio_denominator as u128)
+            .and_then(|amount| u64::try_from(amount).ok())
+    }
+}
+
+/// Optional campaign terms of an airdrop, committed in the airdrop PDA seeds,
+/// see `pda::AirdropSeeds::with_terms`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct AirdropTerms {
+    pub fee: Option<ClaimFee>,
+    pub hook: Option<Pubkey>,
+    pub attestor: Option<Pubkey>,
+    pub denylist: Option<Denylist>,
+    pub expiry_slot: Option<u64>,
+    pub early_unlock: Option<EarlyUnlock>,
+    pub decay: Option<ClaimDecay>,
+}
+
+/// Fee charged on every claim of a campaign.
+///
+/// The fee is committed in the airdrop PDA seeds, a claim that omits or alters
+/// it derives a different PDA and fails.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ClaimFee {
+    pub amount: FeeAmount,
+    pub recipient: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum FeeAmount {
+    /// Lamports transferred from the claimant to the fee recipient.
+    Lamports(u64),
+    /// Basis points of the claimed tokens, compressed to the fee recipient.
+    Bps(u16),
+}
+
+impl ClaimFee {
+    pub const MAX_BPS: u16 = 10_000;
+
+    /// Airdrop PDA seed committing to the fee.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"fee", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+
+    /// Fee in tokens for claiming `claim_amount` tokens.
+    pub fn token_fee(&self, claim_amount: u64) -> u64 {
+        match self.amount {
+            FeeAmount::Lamports(_) => 0,
+            FeeAmount::Bps(bps) => {
+                (claim_amount as u128 * bps as u128 / Self::MAX_BPS as u128) as u64
+            }
+        }
+    }
+}
+
+/// Early unlock terms of a campaign.
+///
+/// Claims before the unlock slot forfeit `penalty_bps` of the claimed tokens
+/// to the treasury until `start_slot`, decaying linearly to zero at the
+/// unlock slot. The terms are committed in the airdrop PDA seeds.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct EarlyUnlock {
+    pub treasury: Pubkey,
+    pub penalty_bps: u16,
+    pub start_slot: u64,
+}
+
+impl EarlyUnlock {
+    /// Airdrop PDA seed committing to the early unlock terms.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"early_unlock", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+
+    /// Penalty in tokens for claiming `claim_amount` tokens at `slot` of an
+    /// airdrop unlocking at `unlock_slot`.
+    pub fn penalty(&self, claim_amount: u64, slot: u64, unlock_slot: u64) -> u64 {
+        if slot >= unlock_slot {
+            return 0;
+        }
+        let max_penalty =
+            claim_amount as u128 * self.penalty_bps as u128 / ClaimFee::MAX_BPS as u128;
+        if slot <= self.start_slot {
+            return max_penalty as u64;
+        }
+        (max_penalty * (unlock_slot - slot) as u128 / (unlock_slot - self.start_slot) as u128)
+            as u64
+    }
+}
+
+/// Decay of the claimable amount of a campaign, to reward early claimers.
+///
+/// From `decay_start_slot` the claimable share of an airdrop shrinks linearly
+/// to zero at `end_slot`, the forfeited share is compressed to the treasury.
+/// The decay is committed in the airdrop PDA seeds.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ClaimDecay {
+    pub treasury: Pubkey,
+    pub decay_start_slot: u64,
+    pub end_slot: u64,
+}
+
+impl ClaimDecay {
+    /// Airdrop PDA seed committing to the decay.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"decay", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+
+    /// Forfeited tokens of `claim_amount` tokens claimed at `slot`.
+    pub fn forfeited_amount(&self, claim_amount: u64, slot: u64) -> u64 {
+        if slot <= self.decay_start_slot {
+            0
+        } else if slot >= self.end_slot {
+            claim_amount
+        } else {
+            (claim_amount as u128 * (slot - self.decay_start_slot) as u128
+                / (self.end_slot - self.decay_start_slot) as u128) as u64
+        }
+    }
+}
+
+/// Bonus for relocking an airdrop, paid from the bonus vault of a funder and
+/// mint.
+///
+/// The bonus is `rate_bps` of the relocked tokens per `period_slots` of added
+/// lock duration. The funder and rate are committed in the bonus vault PDA
+/// seeds, and the funder signs every relock paid from its vault.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RelockBonus {
+    pub funder: Pubkey,
+    pub rate_bps: u16,
+    pub period_slots: u64,
+}
+
+impl RelockBonus {
+    /// Bonus vault PDA seed committing to the funder and rate.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"relock_bonus", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+
+    /// Bonus in tokens for locking `amount` tokens `added_slots` longer.
+    pub fn bonus_amount(&self, amount: u64, added_slots: u64) -> u64 {
+        let bonus_per_period = amount as u128 * self.rate_bps as u128 / ClaimFee::MAX_BPS as u128;
+        (bonus_per_period * added_slots as u128)
+            .checked_div(self.period_slots as u128)
+            .map_or(0, |bonus| u64::try_from(bonus).unwrap_or(u64::MAX))
+    }
+}
+
+/// Token grant from a grantor to an employee, vesting linearly from
+/// `start_slot` to `end_slot`.
+///
+/// The grant is committed in the grant PDA seeds. The grant PDA holds the
+/// unclaimed tokens, so the claimed amount is `amount` minus its balance.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Grant {
+    pub grantor: Pubkey,
+    pub employee: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+impl Grant {
+    /// Grant PDA seed committing to the grant.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"grant", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+
+    /// Amount vested at `slot`.
+    pub fn vested_amount(&self, slot: u64) -> u64 {
+        if slot >= self.end_slot {
+            self.amount
+        } else if slot < self.start_slot {
+            0
+        } else {
+            (self.amount as u128 * (slot - self.start_slot) as u128
+                / (self.end_slot - self.start_slot) as u128) as u64
+        }
+    }
+
+    /// Vested tokens of a grant PDA holding `balance` that are not claimed yet.
+    pub fn claimable_amount(&self, balance: u64, slot: u64) -> u64 {
+        let unvested = self.amount - self.vested_amount(slot);
+        balance.saturating_sub(unvested)
+    }
+}
+
+/// Airdrop or grant whose claimable amount is queried with `GetClaimable`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ClaimSource {
+    Airdrop {
+        claimant: Pubkey,
+        mint: Pubkey,
+        unlock_slot: u64,
+        fee: Option<ClaimFee>,
+        hook: Option<Pubkey>,
+        attestor: Option<Pubkey>,
+        denylist: Option<Denylist>,
+        expiry_slot: Option<u64>,
+        early_unlock: Option<EarlyUnlock>,
+        decay: Option<ClaimDecay>,
+    },
+    Grant(Grant),
+}
+
+impl ClaimSource {
+    /// Amount a claim decompresses at `slot` from a PDA holding `balance`.
+    pub fn claimable(&self, balance: u64, slot: u64) -> Claimable {
+        match self {
+            Self::Airdrop {
+                unlock_slot,
+                fee,
+                expiry_slot,
+                early_unlock,
+                decay,
+                ..
+            } => {
+                // claims are rejected once the claim window or the decay ends.
+                let end_slot = expiry_slot
+                    .iter()
+                    .chain(decay.as_ref().map(|decay| &decay.end_slot))
+                    .min();
+                if end_slot.is_some_and(|end_slot| slot >= *end_slot) {
+                    return Claimable {
+                        amount: 0,
+                        next_unlock_slot: None,
+                        paused: false,
+                    };
+                }
+                if slot < *unlock_slot && early_unlock.is_none() {
+                    return Claimable {
+                        amount: 0,
+                        next_unlock_slot: Some(*unlock_slot),
+                        paused: false,
+                    };
+                }
+                // claims with fees or penalties above `MAX_BPS` fail.
+                let invalid_fee = fee.as_ref().is_some_and(
+                    |fee| matches!(fee.amount, FeeAmount::Bps(bps) if bps > ClaimFee::MAX_BPS),
+                );
+                let invalid_penalty = early_unlock
+                    .as_ref()
+                    .is_some_and(|early_unlock| early_unlock.penalty_bps > ClaimFee::MAX_BPS);
+                if invalid_fee || invalid_penalty {
+                    return Claimable {
+                        amount: 0,
+                        next_unlock_slot: None,
+                        paused: false,
+                    };
+                }
+                let token_fee = fee.as_ref().map_or(0, |fee| fee.token_fee(balance));
+                let net_amount = balance.saturating_sub(token_fee);
+                let penalty = early_unlock.as_ref().map_or(0, |early_unlock| {
+                    early_unlock.penalty(net_amount, slot, *unlock_slot)
+                });
+                let forfeited_amount = decay
+                    .as_ref()
+                    .map_or(0, |decay| decay.forfeited_amount(net_amount, slot));
+                Claimable {
+                    amount: net_amount.saturating_sub(penalty.saturating_add(forfeited_amount)),
+                    next_unlock_slot: (slot < *unlock_slot).then_some(*unlock_slot),
+                    paused: false,
+                }
+            }
+            Self::Grant(grant) => Claimable {
+                amount: grant.claimable_amount(balance, slot),
+                next_unlock_slot: (slot < grant.end_slot).then(|| slot.max(grant.start_slot) + 1),
+                paused: false,
+            },
+        }
+    }
+}
+
+/// Return data of `GetClaimable`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Claimable {
+    /// Amount a claim at the current slot decompresses, net of token fees,
+    /// early unlock penalties and decay forfeits. Zero once claims expired.
+    pub amount: u64,
+    /// Next slot at which more tokens unlock or vest, if any.
+    pub next_unlock_slot: Option<u64>,
+    /// Claims are paused, `amount` becomes claimable once they resume.
+    pub paused: bool,
+}
+
+impl Claimable {
+    pub const MAX_LEN: usize = 8 + (1 + 8) + 1;
+
+    /// Decodes the return data of `GetClaimable`. The runtime trims trailing
+    /// zero bytes of return data, they are restored before decoding.
+    pub fn try_from_return_data(data: &[u8]) -> std::io::Result<Self> {
+        if data.len() > Self::MAX_LEN {
+            return Err(std::io::ErrorKind::InvalidData.into());
+        }
+        let mut padded = [0; Self::MAX_LEN];
+        padded[..data.len()].copy_from_slice(data);
+        Self::deserialize(&mut &padded[..])
+    }
+}
+
+/// M-of-N multisig claimant.
+///
+/// The multisig takes the place of the claimant in the airdrop PDA seeds, see
+/// `AirdropSeeds::new_multisig`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ClaimMultisig {
+    pub threshold: u8,
+    pub signers: Vec<Pubkey>,
+}
+
+impl ClaimMultisig {
+    pub const MAX_SIGNERS: usize = 11;
+
+    /// Airdrop PDA seed committing to the threshold and signer set.
+    pub fn commitment(&self) -> [u8; 32] {
+        hashv(&[b"multisig", &borsh::to_vec(self).unwrap()]).to_bytes()
+    }
+
+    /// The threshold is reachable and signers are unique.
+    pub fn is_valid(&self) -> bool {
+        let unique = self
+            .signers
+            .iter()
+            .enumerate()
+            .all(|(i, signer)| !self.signers[..i].contains(signer));
+        self.threshold > 0
+            && self.threshold as usize <= self.signers.len()
+            && self.signers.len() <= Self::MAX_SIGNERS
+            && unique
+    }
+
+    /// Number of distinct multisig signers among `signed`.
+    pub fn count_signed<'a>(&self, signed: impl Iterator<Item = &'a Pubkey>) -> usize {
+        let mut counted = Vec::with_capacity(self.signers.len());
+        for key in signed {
+            if self.signers.contains(key) && !counted.contains(key) {
+                counted.push(*key);
+            }
+        }
+        counted.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grant_vesting() {
+        let grant = Grant {
+            grantor: Pubkey::new_unique(),
+            employee: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount: 1_000,
+            start_slot: 100,
+            end_slot: 1_100,
+        };
+        assert_eq!(grant.vested_amount(0), 0);
+        assert_eq!(grant.vested_amount(100), 0);
+        assert_eq!(grant.vested_amount(600), 500);
+        assert_eq!(grant.vested_amount(1_100), 1_000);
+        assert_eq!(grant.vested_amount(u64::MAX), 1_000);
+
+        // 300 of 500 vested tokens are claimed already.
+        assert_eq!(grant.claimable_amount(700, 600), 200);
+        assert_eq!(grant.claimable_amount(500, 600), 0);
+    }
+
+    #[test]
+    fn test_early_unlock_penalty() {
+        let early_unlock = EarlyUnlock {
+            treasury: Pubkey::new_unique(),
+            penalty_bps: 5_000,
+            start_slot: 100,
+        };
+        assert_eq!(early_unlock.penalty(1_000, 0, 1_100), 500);
+        assert_eq!(early_unlock.penalty(1_000, 100, 1_100), 500);
+        assert_eq!(early_unlock.penalty(1_000, 600, 1_100), 250);
+        assert_eq!(early_unlock.penalty(1_000, 1_099, 1_100), 0);
+        assert_eq!(early_unlock.penalty(1_000, 1_100, 1_100), 0);
+        assert_eq!(early_unlock.penalty(u64::MAX, 100, 1_100), u64::MAX / 2);
+        // unlocking at the start slot, the penalty applies in full until then.
+        assert_eq!(early_unlock.penalty(1_000, 99, 100), 500);
+    }
+
+    #[test]
+    fn test_decay() {
+        let decay = ClaimDecay {
+            treasury: Pubkey::new_unique(),
+            decay_start_slot: 100,
+            end_slot: 300,
+        };
+        assert_eq!(decay.forfeited_amount(1_000, 0), 0);
+        assert_eq!(decay.forfeited_amount(1_000, 100), 0);
+        assert_eq!(decay.forfeited_amount(1_000, 150), 250);
+        assert_eq!(decay.forfeited_amount(1_000, 299), 995);
+        assert_eq!(decay.forfeited_amount(1_000, 300), 1_000);
+        assert_eq!(decay.forfeited_amount(u64::MAX, 200), u64::MAX / 2);
+    }
+
+    #[test]
+    fn test_relock_bonus() {
+        let bonus = RelockBonus {
+            funder: Pubkey::new_unique(),
+            rate_bps: 100,
+            period_slots: 1_000,
+        };
+        assert_eq!(bonus.bonus_amount(10_000, 0), 0);
+        assert_eq!(bonus.bonus_amount(10_000, 500), 50);
+        assert_eq!(bonus.bonus_amount(10_000, 1_000), 100);
+        assert_eq!(bonus.bonus_amount(10_000, 10_000), 1_000);
+        assert_eq!(bonus.bonus_amount(u64::MAX, u64::MAX), u64::MAX);
+        let no_period = RelockBonus {
+            funder: Pubkey::new_unique(),
+            rate_bps: 100,
+            period_slots: 0,
+        };
+        assert_eq!(no_period.bonus_amount(10_000, 1_000), 0);
+    }
+
+    #[test]
+    fn test_migration_ratio() {
+        let mut migration = Migration {
+            authority: Pubkey::new_unique(),
+            old_mint: Pubkey::new_unique(),
+            new_mint: Pubkey::new_unique(),
+            ratio_numerator: 3,
+            ratio_denominator: 2,
+            migrated_amount: 0,
+            released_amount: 0,
+            bump_seed: 255,
+        };
+        assert_eq!(migration.new_amount(0), Some(0));
+        assert_eq!(migration.new_amount(1), Some(1));
+        assert_eq!(migration.new_amount(10), Some(15));
+        assert_eq!(migration.new_amount(u64::MAX), None);
+        assert_eq!(borsh::to_vec(&migration).unwrap().len(), Migration::LEN);
+        migration.ratio_denominator = 0;
+        assert_eq!(migration.new_amount(10), None);
+    }
+
+    #[test]
+    fn test_multisig_threshold() {
+        let signers = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let multisig = ClaimMultisig {
+            threshold: 2,
+            signers: signers.to_vec(),
+        };
+        assert!(multisig.is_valid());
+        assert_eq!(multisig.count_signed([signers[0]].iter()), 1);
+        // duplicate signatures count once, non-members not at all.
+        assert_eq!(
+            multisig.count_signed([signers[0], signers[0], Pubkey::new_unique()].iter()),
+            1
+        );
+        assert_eq!(multisig.count_signed(signers[1..].iter()), 2);
+
+        let unreachable = ClaimMultisig {
+            threshold: 4,
+            signers: signers.to_vec(),
+        };
+        assert!(!unreachable.is_valid());
+        let duplicate = ClaimMultisig {
+            threshold: 2,
+            signers: vec![signers[0], signers[0]],
+        };
+        assert!(!duplicate.is_valid());
+    }
+
+    #[test]
+    fn test_claimable() {
+        let fee = ClaimFee {
+            amount: FeeAmount::Bps(1_000),
+            recipient: Pubkey::new_unique(),
+        };
+        let airdrop = |expiry_slot, early_unlock, decay| ClaimSource::Airdrop {
+            claimant: Pubkey::default(),
+            mint: Pubkey::default(),
+            unlock_slot: 100,
+            fee: Some(fee),
+            hook: None,
+            attestor: None,
+            denylist: None,
+            expiry_slot,
+            early_unlock,
+            decay,
+        };
+        let plain = airdrop(None, None, None);
+        assert_eq!(
+            plain.claimable(50, 99),
+            Claimable {
+                amount: 0,
+                next_unlock_slot: Some(100),
+                paused: false,
+            }
+        );
+        assert_eq!(plain.claimable(50, 100).amount, 45);
+        assert_eq!(plain.claimable(50, 100).next_unlock_slot, None);
+
+        // 45 after fees, a fifth decayed.
+        let decay = ClaimDecay {
+            treasury: Pubkey::new_unique(),
+            decay_start_slot: 100,
+            end_slot: 150,
+        };
+        let decaying = airdrop(Some(200), None, Some(decay));
+        assert_eq!(decaying.claimable(50, 110).amount, 36);
+        let expired = Claimable {
+            amount: 0,
+            next_unlock_slot: None,
+            paused: false,
+        };
+        assert_eq!(decaying.claimable(50, 150), expired);
+        let expiring = airdrop(Some(120), None, Some(decay));
+        assert_eq!(expiring.claimable(50, 120), expired);
+
+        // a third of the 10% penalty remains before the unlock slot.
+        let early_unlock = EarlyUnlock {
+            treasury: Pubkey::new_unique(),
+            penalty_bps: 1_000,
+            start_slot: 70,
+        };
+        assert_eq!(
+            airdrop(None, Some(early_unlock), None).claimable(50, 90),
+            Claimable {
+                amount: 44,
+                next_unlock_slot: Some(100),
+                paused: false,
+            }
+        );
+
+        let grant = ClaimSource::Grant(Grant {
+            grantor: Pubkey::new_unique(),
+            employee: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount: 100,
+            start_slot: 10,
+            end_slot: 20,
+        });
+        assert_eq!(grant.claimable(100, 5).amount, 0);
+        assert_eq!(grant.claimable(100, 5).next_unlock_slot, Some(11));
+        // 30 vested, 20 of them claimed.
+        assert_eq!(grant.claimable(80, 13).amount, 10);
+        assert_eq!(grant.claimable(80, 13).next_unlock_slot, Some(14));
+        assert_eq!(grant.claimable(80, 20).next_unlock_slot, None);
+    }
+
+    #[test]
+    fn test_claimable_return_data() {
+        let trimmed = |claimable: &Claimable| {
+            let mut data = borsh::to_vec(claimable).unwrap();
+            while data.last() == Some(&0) {
+                data.pop();
+            }
+            data
+        };
+        for claimable in [
+            Claimable {
+                amount: 0,
+                next_unlock_slot: None,
+                paused: false,
+            },
+            Claimable {
+                amount: 45,
+                next_unlock_slot: None,
+                paused: false,
+            },
+            Claimable {
+                amount: 45,
+                next_unlock_slot: Some(256),
+                paused: false,
+            },
+            Claimable {
+                amount: 45,
+                next_unlock_slot: Some(256),
+                paused: true,
+            },
+        ] {
+            assert_eq!(
+                Claimable::try_from_return_data(&trimmed(&claimable)).unwrap(),
+                claimable
+            );
+        }
+        assert!(Claimable::try_from_return_data(&[1; Claimable::MAX_LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn test_claimable_invalid_bps() {
+        let airdrop = |bps, penalty_bps| ClaimSource::Airdrop {
+            claimant: Pubkey::default(),
+            mint: Pubkey::default(),
+            unlock_slot: 100,
+            fee: Some(ClaimFee {
+                amount: FeeAmount::Bps(bps),
+                recipient: Pubkey::new_unique(),
+            }),
+            hook: None,
+            attestor: None,
+            denylist: None,
+            expiry_slot: None,
+            early_unlock: Some(EarlyUnlock {
+                treasury: Pubkey::new_unique(),
+                penalty_bps,
+                start_slot: 60,
+            }),
+            decay: Some(ClaimDecay {
+                treasury: Pubkey::new_unique(),
+                decay_start_slot: 0,
+                end_slot: 200,
+            }),
+        };
+        // fees and penalties above `MAX_BPS` fail the claim.
+        assert_eq!(airdrop(u16::MAX, 0).claimable(50, 100).amount, 0);
+        assert_eq!(airdrop(0, u16::MAX).claimable(50, 100).amount, 0);
+        // the full penalty and a quarter decayed add up past `u64::MAX`.
+        assert_eq!(
+            airdrop(0, ClaimFee::MAX_BPS).claimable(u64::MAX, 50).amount,
+            0
+        );
+    }
+}
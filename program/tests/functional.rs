@@ -1,9 +1,34 @@
 #![cfg(feature = "test-sbf")]
 
-use light_client::indexer::GetCompressedTokenAccountsByOwnerOrDelegateOptions;
+use borsh::BorshDeserialize;
+use light_client::indexer::{AddressWithTree, GetCompressedTokenAccountsByOwnerOrDelegateOptions};
 use light_compressed_account::compressed_account::PackedMerkleContext;
 use light_compressed_account::constants::ACCOUNT_COMPRESSION_PROGRAM_ID;
-use light_compressed_claim::instruction::{build_claim_and_decompress_instruction, ClaimAccounts};
+use light_compressed_account::instruction_data::compressed_proof::CompressedProof;
+use light_compressed_claim::attestation::{build_attestation_instruction, Attestation};
+use light_compressed_claim::instruction::{
+    build_accept_admin_instruction, build_add_to_denylist_instruction,
+    build_airdrop_lamports_instruction, build_claim_and_decompress_instruction,
+    build_claim_basket_instruction, build_claim_grant_instruction,
+    build_claim_lamports_instruction, build_claim_multisig_instruction,
+    build_claim_with_hook_instruction, build_create_grant_instruction,
+    build_create_migration_instruction, build_early_claim_instruction,
+    build_get_claimable_instruction, build_initialize_config_instruction, build_merge_instruction,
+    build_migrate_instruction, build_relock_instruction, build_remove_from_denylist_instruction,
+    build_revoke_grant_instruction, build_set_paused_instruction, build_transfer_admin_instruction,
+    build_transfer_beneficiary_instruction, build_withdraw_migration_instruction, BasketClaim,
+    ClaimAccounts, ClaimLamportsAccounts, ClaimProgramInstruction, DenylistAccounts, MergePosition,
+    MigrationInput, MigrationSource, PackedClaimAccounts, RelockBonusInput,
+};
+use light_compressed_claim::pda::{
+    derive_denylist_entry_address, find_airdrop_pda, find_bonus_vault_pda, find_config_pda,
+    find_grant_pda, find_lamports_airdrop_pda, find_migration_pda, AirdropSeeds, CONFIG_SEED,
+};
+use light_compressed_claim::state::{
+    AirdropTerms, ClaimConfig, ClaimDecay, ClaimFee, ClaimMultisig, ClaimSource, Claimable,
+    Denylist, DenylistCheck, DenylistEntryProof, EarlyUnlock, FeeAmount, Grant, Migration,
+    RelockBonus,
+};
 use light_compressed_token::mint_sdk::create_create_token_pool_instruction;
 use light_compressed_token_client::instructions::compress;
 use light_compressed_token_client::{get_token_pool_pda, LIGHT_SYSTEM_PROGRAM_ID};
@@ -13,8 +38,11 @@ use light_program_test::{
     program_test::LightProgramTest, Indexer, ProgramTestConfig, RpcConnection,
 };
 use solana_program_test::tokio;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
 use solana_sdk::{program_pack::Pack, system_instruction};
 use spl_token::{
     id, instruction,
@@ -22,7 +50,2680 @@ use spl_token::{
 };
 
 #[tokio::test]
-async fn test_claim_and_decompress() {
+async fn test_claim_and_decompress() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let amount = 2;
+    let (instruction, token_account) =
+        setup_airdrop_claim(&mut rpc, &claimant, unlock_slot, amount, None).await;
+    let instruction_clone = instruction.clone();
+
+    // SPL token account should be without the compressed tokens.
+    assert_eq!(
+        get_token_balance(&mut rpc, &token_account).await,
+        10 - amount
+    );
+
+    // not yet unlocked.
+    rpc.warp_to_slot(999).unwrap();
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x1"
+    );
+
+    // now unlocked.
+    rpc.warp_to_slot(1000).unwrap();
+    rpc.create_and_send_transaction(&[instruction_clone], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_pause_and_unpause_claims() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    let admin = Keypair::new();
+    initialize_config(&mut rpc, admin.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let (instruction, token_account) = setup_airdrop_claim(&mut rpc, &claimant, 0, 2, None).await;
+
+    // paused claims fail.
+    let pause_ix = build_set_paused_instruction(admin.pubkey(), true);
+    rpc.create_and_send_transaction(&[pause_ix], &payer.pubkey(), &[&payer, &admin])
+        .await
+        .unwrap();
+    let result = rpc
+        .create_and_send_transaction(
+            &[instruction.clone()],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x3"
+    );
+
+    // unpaused claims succeed.
+    let unpause_ix = build_set_paused_instruction(admin.pubkey(), false);
+    rpc.create_and_send_transaction(&[unpause_ix], &payer.pubkey(), &[&payer, &admin])
+        .await
+        .unwrap();
+    // new blockhash, so that the retried claim is not a duplicate transaction.
+    rpc.warp_to_slot(100).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_admin_authorization() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    let admin = Keypair::new();
+    let new_admin = Keypair::new();
+    let attacker = Keypair::new();
+    let unauthorized =
+        "TransactionError: Error processing Instruction 0: custom program error: 0x4";
+
+    // only the upgrade authority can initialize the config.
+    set_upgrade_authority(&mut rpc, &payer.pubkey()).await;
+    let initialize_ix = build_initialize_config_instruction(attacker.pubkey(), attacker.pubkey());
+    let result = rpc
+        .create_and_send_transaction(&[initialize_ix], &payer.pubkey(), &[&payer, &attacker])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), unauthorized);
+
+    // funding the config PDA in advance doesn't block its creation.
+    let prefund_ix = system_instruction::transfer(&payer.pubkey(), &find_config_pda().0, 1_000_000);
+    rpc.create_and_send_transaction(&[prefund_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    initialize_config(&mut rpc, admin.pubkey()).await;
+
+    // only the admin can pause.
+    let pause_ix = build_set_paused_instruction(attacker.pubkey(), true);
+    let result = rpc
+        .create_and_send_transaction(&[pause_ix], &payer.pubkey(), &[&payer, &attacker])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), unauthorized);
+
+    // only the admin can propose a new admin.
+    let transfer_ix = build_transfer_admin_instruction(attacker.pubkey(), attacker.pubkey());
+    let result = rpc
+        .create_and_send_transaction(&[transfer_ix], &payer.pubkey(), &[&payer, &attacker])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), unauthorized);
+
+    let transfer_ix = build_transfer_admin_instruction(admin.pubkey(), new_admin.pubkey());
+    rpc.create_and_send_transaction(&[transfer_ix], &payer.pubkey(), &[&payer, &admin])
+        .await
+        .unwrap();
+
+    // only the pending admin can accept.
+    let accept_ix = build_accept_admin_instruction(attacker.pubkey());
+    let result = rpc
+        .create_and_send_transaction(&[accept_ix], &payer.pubkey(), &[&payer, &attacker])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), unauthorized);
+
+    // the old admin stays admin until the transfer is accepted.
+    let pause_ix = build_set_paused_instruction(new_admin.pubkey(), true);
+    let result = rpc
+        .create_and_send_transaction(&[pause_ix], &payer.pubkey(), &[&payer, &new_admin])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), unauthorized);
+
+    let accept_ix = build_accept_admin_instruction(new_admin.pubkey());
+    rpc.create_and_send_transaction(&[accept_ix], &payer.pubkey(), &[&payer, &new_admin])
+        .await
+        .unwrap();
+
+    let pause_ix = build_set_paused_instruction(admin.pubkey(), true);
+    let result = rpc
+        .create_and_send_transaction(&[pause_ix], &payer.pubkey(), &[&payer, &admin])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), unauthorized);
+
+    rpc.warp_to_slot(100).unwrap();
+    let pause_ix = build_set_paused_instruction(new_admin.pubkey(), true);
+    rpc.create_and_send_transaction(&[pause_ix], &payer.pubkey(), &[&payer, &new_admin])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_non_canonical_config() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    let admin = Keypair::new();
+    initialize_config(&mut rpc, admin.pubkey()).await;
+    let invalid_config =
+        "TransactionError: Error processing Instruction 0: custom program error: 0x5";
+
+    let claimant = Keypair::new();
+    let (mut instruction, _) = setup_airdrop_claim(&mut rpc, &claimant, 0, 2, None).await;
+    let pause_ix = build_set_paused_instruction(admin.pubkey(), true);
+    rpc.create_and_send_transaction(&[pause_ix], &payer.pubkey(), &[&payer, &admin])
+        .await
+        .unwrap();
+
+    // an unpaused config at a non-canonical bump seed of the config PDA.
+    let (config, canonical_bump) = find_config_pda();
+    let (non_canonical_config, bump_seed) = (0..canonical_bump)
+        .rev()
+        .find_map(|bump| {
+            Pubkey::create_program_address(&[CONFIG_SEED, &[bump]], &light_compressed_claim::id())
+                .ok()
+                .map(|pda| (pda, bump))
+        })
+        .unwrap();
+    let mut data = borsh::to_vec(&ClaimConfig {
+        admin: admin.pubkey(),
+        pending_admin: None,
+        paused: false,
+        bump_seed,
+    })
+    .unwrap();
+    data.resize(ClaimConfig::LEN, 0);
+    let rent = rpc.context.banks_client.get_rent().await.unwrap();
+    let account = solana_sdk::account::Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: light_compressed_claim::id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    rpc.context
+        .set_account(&non_canonical_config, &account.into());
+
+    // claims can't bypass the pause with the non-canonical config.
+    let config_meta = instruction
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == config)
+        .unwrap();
+    config_meta.pubkey = non_canonical_config;
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), invalid_config);
+
+    // nor can a second config be created at a non-canonical bump seed.
+    let mut initialize_ix = build_initialize_config_instruction(payer.pubkey(), payer.pubkey());
+    initialize_ix.accounts[1].pubkey = non_canonical_config;
+    let result = rpc
+        .create_and_send_transaction(&[initialize_ix], &payer.pubkey(), &[&payer])
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), invalid_config);
+}
+
+#[tokio::test]
+async fn test_claim_with_token_fee() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let fee = ClaimFee {
+        amount: FeeAmount::Bps(1_000),
+        recipient: Pubkey::new_unique(),
+    };
+    let (instruction, token_account) =
+        setup_airdrop_claim(&mut rpc, &claimant, 0, 10, Some(fee)).await;
+
+    // the fee can't be skipped, it is committed in the airdrop PDA seeds.
+    let mut instruction_without_fee = instruction.clone();
+    let mut data = ClaimProgramInstruction::try_from_slice(&instruction.data).unwrap();
+    if let ClaimProgramInstruction::Claim { fee, .. } = &mut data {
+        *fee = None;
+    }
+    instruction_without_fee.data = borsh::to_vec(&data).unwrap();
+    let result = rpc
+        .create_and_send_transaction(
+            &[instruction_without_fee],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x2"
+    );
+
+    // nor passed off as another term with the same seed bytes.
+    let fee_as_key = Pubkey::new_from_array(fee.commitment());
+    let mut instruction_with_swapped_term = instruction.clone();
+    let mut data = ClaimProgramInstruction::try_from_slice(&instruction.data).unwrap();
+    if let ClaimProgramInstruction::Claim { fee, denylist, .. } = &mut data {
+        *fee = None;
+        *denylist = Some(DenylistCheck {
+            denylist: Denylist {
+                authority: fee_as_key,
+                address_tree: fee_as_key,
+            },
+            entry: DenylistEntryProof::Absent {
+                address_tree_index: 0,
+                address_root_index: 0,
+            },
+            cpi_context_index: 0,
+        });
+    }
+    instruction_with_swapped_term.data = borsh::to_vec(&data).unwrap();
+    instruction_with_swapped_term.accounts.extend([
+        AccountMeta::new_readonly(
+            Pubkey::find_program_address(&[b"cpi_authority"], &light_compressed_claim::id()).0,
+            false,
+        ),
+        AccountMeta::new_readonly(light_compressed_claim::id(), false),
+    ]);
+    let result = rpc
+        .create_and_send_transaction(
+            &[instruction_with_swapped_term],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x2"
+    );
+
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    // 10% of the claimed tokens stay compressed, owned by the fee recipient.
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 9);
+    let fee_accounts = rpc
+        .get_compressed_token_accounts_by_owner(&fee.recipient, None, None)
+        .await
+        .unwrap()
+        .value
+        .items;
+    assert_eq!(fee_accounts.len(), 1);
+    assert_eq!(fee_accounts[0].token.amount, 1);
+}
+
+#[tokio::test]
+async fn test_claim_with_lamport_fee() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    rpc.airdrop_lamports(&claimant.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    let fee_lamports = 5_000_000;
+    let fee = ClaimFee {
+        amount: FeeAmount::Lamports(fee_lamports),
+        recipient: Pubkey::new_unique(),
+    };
+    let (instruction, token_account) =
+        setup_airdrop_claim(&mut rpc, &claimant, 0, 10, Some(fee)).await;
+
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+    let recipient = rpc
+        .context
+        .banks_client
+        .get_account(fee.recipient)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(recipient.lamports, fee_lamports);
+}
+
+#[tokio::test]
+async fn test_grant_claim_and_revoke() {
+    let mut rpc = setup_rpc().await;
+    let grantor = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, grantor.pubkey()).await;
+
+    let (mint, token_account, _) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &mint).await;
+
+    let employee = Keypair::new();
+    let grant = Grant {
+        grantor: grantor.pubkey(),
+        employee: employee.pubkey(),
+        mint: mint.pubkey(),
+        amount: 10,
+        start_slot: 100,
+        end_slot: 1100,
+    };
+    let (grant_pda, bump_seed) = find_grant_pda(&grant);
+
+    let accounts = claim_accounts(
+        &rpc,
+        grantor.pubkey(),
+        grant_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_create_grant_instruction(&accounts, grant, bump_seed);
+    rpc.create_and_send_transaction(&[instruction], &grantor.pubkey(), &[&grantor])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 0);
+
+    // half of the grant is vested.
+    rpc.warp_to_slot(600).unwrap();
+    let input = get_claim_input(&mut rpc, &grant_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        employee.pubkey(),
+        grant_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_claim_grant_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        grant,
+        bump_seed,
+    );
+    rpc.create_and_send_transaction(&[instruction], &grantor.pubkey(), &[&grantor, &employee])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 5);
+
+    // at slot 700 6 tokens are vested, revoke at slot 850, when 7 are.
+    rpc.warp_to_slot(700).unwrap();
+    let revoke_slot = 850;
+    let input = get_claim_input(&mut rpc, &grant_pda, &mint.pubkey()).await;
+    assert_eq!(input.amount, 5);
+    let accounts = claim_accounts(
+        &rpc,
+        grantor.pubkey(),
+        grant_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let build_revoke = |revoke_slot| {
+        build_revoke_grant_instruction(
+            &accounts,
+            input.proof,
+            input.root_index,
+            input.merkle_context,
+            input.amount,
+            None,
+            grant,
+            bump_seed,
+            revoke_slot,
+        )
+    };
+
+    // a revoke slot after the end slot is rejected.
+    let result = rpc
+        .create_and_send_transaction(
+            &[build_revoke(grant.end_slot + 1)],
+            &grantor.pubkey(),
+            &[&grantor],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x9"
+    );
+
+    rpc.create_and_send_transaction(&[build_revoke(revoke_slot)], &grantor.pubkey(), &[&grantor])
+        .await
+        .unwrap();
+
+    let returned = get_claim_input(&mut rpc, &grantor.pubkey(), &mint.pubkey()).await;
+    assert_eq!(returned.amount, 3);
+
+    // the token vested by now unlocks at the start slot of the grant, the one
+    // vesting until the revoke slot unlocks at the revoke slot.
+    for (unlock_slot, locked) in [(grant.start_slot, false), (revoke_slot, true)] {
+        let (employee_airdrop_pda, employee_bump_seed) =
+            find_airdrop_pda(&employee.pubkey(), &mint.pubkey(), unlock_slot);
+        let accounts = claim_accounts(
+            &rpc,
+            employee.pubkey(),
+            employee_airdrop_pda,
+            &mint.pubkey(),
+            token_account.pubkey(),
+        );
+        let vested = get_claim_input(&mut rpc, &employee_airdrop_pda, &mint.pubkey()).await;
+        assert_eq!(vested.amount, 1);
+        let instruction = build_claim_and_decompress_instruction(
+            &accounts,
+            vested.proof,
+            vested.root_index,
+            vested.merkle_context,
+            vested.amount,
+            None,
+            mint.pubkey(),
+            unlock_slot,
+            employee_bump_seed,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        if locked {
+            let result = rpc
+                .create_and_send_transaction(
+                    &[instruction.clone()],
+                    &grantor.pubkey(),
+                    &[&grantor, &employee],
+                )
+                .await;
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "TransactionError: Error processing Instruction 0: custom program error: 0x1"
+            );
+            rpc.warp_to_slot(revoke_slot).unwrap();
+        }
+        rpc.create_and_send_transaction(&[instruction], &grantor.pubkey(), &[&grantor, &employee])
+            .await
+            .unwrap();
+    }
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 7);
+}
+
+#[tokio::test]
+async fn test_transfer_beneficiary() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let new_claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), unlock_slot, 2, None).await;
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_transfer_beneficiary_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        AirdropTerms::default(),
+        new_claimant.pubkey(),
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    // the new claimant claims once unlocked.
+    rpc.warp_to_slot(unlock_slot).unwrap();
+    let (new_airdrop_pda, new_bump_seed) =
+        find_airdrop_pda(&new_claimant.pubkey(), &mint.pubkey(), unlock_slot);
+    let input = get_claim_input(&mut rpc, &new_airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        new_claimant.pubkey(),
+        new_airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        new_bump_seed,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &new_claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_transfer_beneficiary_terms() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+
+    let claimant = Keypair::new();
+    let new_claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let terms = AirdropTerms {
+        fee: Some(ClaimFee {
+            amount: FeeAmount::Bps(1_000),
+            recipient: Pubkey::new_unique(),
+        }),
+        expiry_slot: Some(2_000),
+        decay: Some(ClaimDecay {
+            treasury: Pubkey::new_unique(),
+            decay_start_slot: 1_500,
+            end_slot: 3_000,
+        }),
+        ..AirdropTerms::default()
+    };
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 2, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, unlock_slot).with_terms(&terms)
+        })
+        .await;
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let transfer = |terms| {
+        build_transfer_beneficiary_instruction(
+            &accounts,
+            input.proof,
+            input.root_index,
+            input.merkle_context,
+            input.amount,
+            None,
+            mint.pubkey(),
+            unlock_slot,
+            bump_seed,
+            terms,
+            new_claimant.pubkey(),
+        )
+    };
+
+    // airdrops with a denylist can't be transferred.
+    let result = rpc
+        .create_and_send_transaction(
+            &[transfer(AirdropTerms {
+                denylist: Some(Denylist {
+                    authority: Pubkey::new_unique(),
+                    address_tree: Pubkey::new_unique(),
+                }),
+                ..terms
+            })],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: invalid program argument"
+    );
+
+    // dropping a term derives another airdrop PDA.
+    let result = rpc
+        .create_and_send_transaction(
+            &[transfer(AirdropTerms {
+                decay: None,
+                ..terms
+            })],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x2"
+    );
+
+    rpc.create_and_send_transaction(&[transfer(terms)], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+
+    // the new airdrop PDA commits to all terms of the campaign.
+    let (new_airdrop_pda, _) =
+        AirdropSeeds::new(&new_claimant.pubkey(), &mint.pubkey(), unlock_slot)
+            .with_terms(&terms)
+            .find_program_address();
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &new_airdrop_pda, &mint.pubkey()).await,
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_relock() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let unlock_slot = 100;
+    let new_unlock_slot = 1_100;
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), unlock_slot, 2, None).await;
+
+    // the campaign funds the bonus vault: 100% per 1000 added slots.
+    let funder = Keypair::new();
+    let bonus = RelockBonus {
+        funder: funder.pubkey(),
+        rate_bps: 10_000,
+        period_slots: 1_000,
+    };
+    let (bonus_vault_pda, bonus_bump_seed) = find_bonus_vault_pda(&mint.pubkey(), &bonus);
+    let compress_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        5,
+        bonus_vault_pda,
+        rpc.test_accounts.v1_state_trees[0].merkle_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let bonus_input = get_claim_input(&mut rpc, &bonus_vault_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let relock = |new_unlock_slot| {
+        build_relock_instruction(
+            &accounts,
+            input.proof,
+            input.root_index,
+            input.merkle_context,
+            input.amount,
+            None,
+            mint.pubkey(),
+            unlock_slot,
+            bump_seed,
+            None,
+            new_unlock_slot,
+            Some(RelockBonusInput {
+                proof: bonus_input.proof,
+                root_index: bonus_input.root_index,
+                merkle_context: bonus_input.merkle_context,
+                amount: bonus_input.amount,
+                lamports: None,
+                bonus,
+                bump_seed: bonus_bump_seed,
+            }),
+        )
+    };
+
+    // relocking can only extend the lock.
+    let result = rpc
+        .create_and_send_transaction(
+            &[relock(unlock_slot)],
+            &payer.pubkey(),
+            &[&payer, &claimant, &funder],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x15"
+    );
+
+    // the bonus is only paid with the signature of the funder.
+    let mut unsigned_relock = relock(new_unlock_slot);
+    unsigned_relock.accounts.last_mut().unwrap().is_signer = false;
+    let result = rpc
+        .create_and_send_transaction(&[unsigned_relock], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x0"
+    );
+
+    rpc.create_and_send_transaction(
+        &[relock(new_unlock_slot)],
+        &payer.pubkey(),
+        &[&payer, &claimant, &funder],
+    )
+    .await
+    .unwrap();
+
+    // 1000 slots added to the lock of 2 tokens earn a bonus of 2 tokens.
+    let (new_airdrop_pda, _) =
+        find_airdrop_pda(&claimant.pubkey(), &mint.pubkey(), new_unlock_slot);
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &new_airdrop_pda, &mint.pubkey()).await,
+        4
+    );
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &bonus_vault_pda, &mint.pubkey()).await,
+        3
+    );
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &airdrop_pda, &mint.pubkey()).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_relock_bonus_of_other_campaign() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    // an unrelated claimant airdrops dust to itself.
+    let claimant = Keypair::new();
+    let unlock_slot = 100;
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), unlock_slot, 1, None).await;
+
+    // a campaign funds a bonus vault of the mint.
+    let funder = Keypair::new();
+    let bonus = RelockBonus {
+        funder: funder.pubkey(),
+        rate_bps: 10_000,
+        period_slots: 1,
+    };
+    let (bonus_vault_pda, bonus_bump_seed) = find_bonus_vault_pda(&mint.pubkey(), &bonus);
+    let compress_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        5,
+        bonus_vault_pda,
+        rpc.test_accounts.v1_state_trees[0].merkle_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let bonus_input = get_claim_input(&mut rpc, &bonus_vault_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let relock = |funder: Pubkey| {
+        build_relock_instruction(
+            &accounts,
+            input.proof,
+            input.root_index,
+            input.merkle_context,
+            input.amount,
+            None,
+            mint.pubkey(),
+            unlock_slot,
+            bump_seed,
+            None,
+            1_100,
+            Some(RelockBonusInput {
+                proof: bonus_input.proof,
+                root_index: bonus_input.root_index,
+                merkle_context: bonus_input.merkle_context,
+                amount: bonus_input.amount,
+                lamports: None,
+                bonus: RelockBonus { funder, ..bonus },
+                bump_seed: bonus_bump_seed,
+            }),
+        )
+    };
+
+    // without the signature of the funder the relock is rejected.
+    let mut unsigned_relock = relock(funder.pubkey());
+    unsigned_relock.accounts.last_mut().unwrap().is_signer = false;
+    let result = rpc
+        .create_and_send_transaction(&[unsigned_relock], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x0"
+    );
+
+    // signing as funder derives another bonus vault.
+    let mut self_funded_relock = relock(claimant.pubkey());
+    self_funded_relock.accounts[self_funded_relock.accounts.len() - 2].pubkey = bonus_vault_pda;
+    let result = rpc
+        .create_and_send_transaction(&[self_funded_relock], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x16"
+    );
+
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &bonus_vault_pda, &mint.pubkey()).await,
+        5
+    );
+}
+
+#[tokio::test]
+async fn test_merge() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), 100, 2, None).await;
+    let (latest_airdrop_pda, _) = find_airdrop_pda(&claimant.pubkey(), &mint.pubkey(), 500);
+    let compress_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        3,
+        latest_airdrop_pda,
+        rpc.test_accounts.v1_state_trees[0].merkle_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        latest_airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let position = MergePosition {
+        proof: input.proof,
+        root_index: input.root_index,
+        merkle_context: input.merkle_context,
+        amount: input.amount,
+        lamports: None,
+        unlock_slot: 100,
+        bump_seed,
+    };
+
+    // merging can't shorten a lock.
+    let result = rpc
+        .create_and_send_transaction(
+            &[build_merge_instruction(
+                &accounts,
+                mint.pubkey(),
+                50,
+                None,
+                vec![position.clone()],
+            )],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x15"
+    );
+
+    rpc.create_and_send_transaction(
+        &[build_merge_instruction(
+            &accounts,
+            mint.pubkey(),
+            500,
+            None,
+            vec![position],
+        )],
+        &payer.pubkey(),
+        &[&payer, &claimant],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &latest_airdrop_pda, &mint.pubkey()).await,
+        5
+    );
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &airdrop_pda, &mint.pubkey()).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_migration() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let (old_mint, old_token_account, _) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &old_mint).await;
+    let (new_mint, new_token_account, _) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &new_mint).await;
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+
+    // another authority's migration of the same mints doesn't block it.
+    let squatter = Keypair::new();
+    rpc.airdrop_lamports(&squatter.pubkey(), 1_000_000_000)
+        .await
+        .unwrap();
+    rpc.create_and_send_transaction(
+        &[build_create_migration_instruction(
+            squatter.pubkey(),
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            1,
+            1,
+        )],
+        &squatter.pubkey(),
+        &[&squatter],
+    )
+    .await
+    .unwrap();
+
+    // 2 new tokens per old token, created even though the PDA is funded.
+    let (migration_pda, _) =
+        find_migration_pda(&payer.pubkey(), &old_mint.pubkey(), &new_mint.pubkey());
+    let prefund_ix = system_instruction::transfer(&payer.pubkey(), &migration_pda, 1_000_000);
+    rpc.create_and_send_transaction(&[prefund_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    rpc.create_and_send_transaction(
+        &[build_create_migration_instruction(
+            payer.pubkey(),
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            2,
+            1,
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    // the campaign funds the migration vault with 8 new tokens.
+    let fund_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        new_token_account.pubkey(),
+        new_mint.pubkey(),
+        8,
+        migration_pda,
+        state_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[fund_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    let accounts = claim_accounts(
+        &rpc,
+        payer.pubkey(),
+        migration_pda,
+        &new_mint.pubkey(),
+        new_token_account.pubkey(),
+    );
+    let vault_input = |input: ClaimInput| MigrationInput {
+        proof: input.proof,
+        root_index: input.root_index,
+        merkle_context: input.merkle_context,
+        amount: input.amount,
+        lamports: None,
+    };
+
+    // migrates 3 SPL old tokens to 6 new tokens.
+    let vault = get_claim_input(&mut rpc, &migration_pda, &new_mint.pubkey()).await;
+    rpc.create_and_send_transaction(
+        &[build_migrate_instruction(
+            &accounts,
+            payer.pubkey(),
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            3,
+            MigrationSource::Spl,
+            vault_input(vault),
+            Some(old_token_account.pubkey()),
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &old_token_account).await, 7);
+    assert_eq!(get_token_balance(&mut rpc, &new_token_account).await, 8);
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &migration_pda, &old_mint.pubkey()).await,
+        3
+    );
+
+    // the 2 new tokens left in the vault can't cover 2 old tokens.
+    let vault = get_claim_input(&mut rpc, &migration_pda, &new_mint.pubkey()).await;
+    let result = rpc
+        .create_and_send_transaction(
+            &[build_migrate_instruction(
+                &accounts,
+                payer.pubkey(),
+                old_mint.pubkey(),
+                new_mint.pubkey(),
+                2,
+                MigrationSource::Spl,
+                vault_input(vault),
+                Some(old_token_account.pubkey()),
+            )],
+            &payer.pubkey(),
+            &[&payer],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x18"
+    );
+
+    // migrates 1 of 2 compressed old tokens, the change returns to the payer.
+    let compress_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        old_token_account.pubkey(),
+        old_mint.pubkey(),
+        2,
+        payer.pubkey(),
+        state_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let old_input = get_claim_input(&mut rpc, &payer.pubkey(), &old_mint.pubkey()).await;
+    let vault = get_claim_input(&mut rpc, &migration_pda, &new_mint.pubkey()).await;
+    rpc.create_and_send_transaction(
+        &[build_migrate_instruction(
+            &accounts,
+            payer.pubkey(),
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            1,
+            MigrationSource::Compressed(vault_input(old_input)),
+            vault_input(vault),
+            None,
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &new_token_account).await, 10);
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &payer.pubkey(), &old_mint.pubkey()).await,
+        1
+    );
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &migration_pda, &old_mint.pubkey()).await,
+        4
+    );
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &migration_pda, &new_mint.pubkey()).await,
+        0
+    );
+
+    let migration_account = rpc
+        .context
+        .banks_client
+        .get_account(migration_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let migration = Migration::deserialize(&mut &migration_account.data[..]).unwrap();
+    assert_eq!(migration.migrated_amount, 4);
+    assert_eq!(migration.released_amount, 8);
+}
+
+#[tokio::test]
+async fn test_withdraw_migration() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let (old_mint, old_token_account, _) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &old_mint).await;
+    let (new_mint, new_token_account, _) = setup_spl_token_account(&mut rpc).await;
+    setup_token_pool(&mut rpc, &new_mint).await;
+
+    // 3 old tokens migrate to 6 of the 8 new tokens in the vault.
+    let (migration_pda, _) =
+        find_migration_pda(&payer.pubkey(), &old_mint.pubkey(), &new_mint.pubkey());
+    let fund_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        new_token_account.pubkey(),
+        new_mint.pubkey(),
+        8,
+        migration_pda,
+        rpc.test_accounts.v1_state_trees[0].merkle_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(
+        &[
+            build_create_migration_instruction(
+                payer.pubkey(),
+                old_mint.pubkey(),
+                new_mint.pubkey(),
+                2,
+                1,
+            ),
+            fund_ix,
+        ],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    let migration_input = |input: ClaimInput| MigrationInput {
+        proof: input.proof,
+        root_index: input.root_index,
+        merkle_context: input.merkle_context,
+        amount: input.amount,
+        lamports: None,
+    };
+    let accounts = claim_accounts(
+        &rpc,
+        payer.pubkey(),
+        migration_pda,
+        &new_mint.pubkey(),
+        new_token_account.pubkey(),
+    );
+    let vault = get_claim_input(&mut rpc, &migration_pda, &new_mint.pubkey()).await;
+    rpc.create_and_send_transaction(
+        &[build_migrate_instruction(
+            &accounts,
+            payer.pubkey(),
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            3,
+            MigrationSource::Spl,
+            migration_input(vault),
+            Some(old_token_account.pubkey()),
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &old_token_account).await, 7);
+    assert_eq!(get_token_balance(&mut rpc, &new_token_account).await, 8);
+
+    // only the migration authority can withdraw.
+    let attacker = Keypair::new();
+    let escrow =
+        migration_input(get_claim_input(&mut rpc, &migration_pda, &old_mint.pubkey()).await);
+    let mut old_accounts = claim_accounts(
+        &rpc,
+        attacker.pubkey(),
+        migration_pda,
+        &old_mint.pubkey(),
+        old_token_account.pubkey(),
+    );
+    let mut instruction = build_withdraw_migration_instruction(
+        &old_accounts,
+        old_mint.pubkey(),
+        new_mint.pubkey(),
+        old_mint.pubkey(),
+        escrow.clone(),
+    );
+    instruction.accounts[2].pubkey = migration_pda;
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &attacker])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x4"
+    );
+
+    // the authority withdraws the escrowed old tokens and the 2 new tokens
+    // left in the vault.
+    old_accounts.claimant = payer.pubkey();
+    rpc.create_and_send_transaction(
+        &[build_withdraw_migration_instruction(
+            &old_accounts,
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            old_mint.pubkey(),
+            escrow,
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    let vault = get_claim_input(&mut rpc, &migration_pda, &new_mint.pubkey()).await;
+    rpc.create_and_send_transaction(
+        &[build_withdraw_migration_instruction(
+            &accounts,
+            old_mint.pubkey(),
+            new_mint.pubkey(),
+            new_mint.pubkey(),
+            migration_input(vault),
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &old_token_account).await, 10);
+    assert_eq!(get_token_balance(&mut rpc, &new_token_account).await, 10);
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &migration_pda, &old_mint.pubkey()).await,
+        0
+    );
+    assert_eq!(
+        get_compressed_balance(&mut rpc, &migration_pda, &new_mint.pubkey()).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_claim_multisig() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let multisig = ClaimMultisig {
+        threshold: 2,
+        signers: signers.iter().map(|signer| signer.pubkey()).collect(),
+    };
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 2, |mint| {
+            AirdropSeeds::new_multisig(&multisig, mint, 0)
+        })
+        .await;
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        signers[0].pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let build_instruction = |other_signers: &[Pubkey]| {
+        build_claim_multisig_instruction(
+            &accounts,
+            other_signers,
+            input.proof,
+            input.root_index,
+            input.merkle_context,
+            input.amount,
+            None,
+            mint.pubkey(),
+            0,
+            bump_seed,
+            None,
+            multisig.clone(),
+        )
+    };
+
+    // a single signer is below the threshold.
+    let result = rpc
+        .create_and_send_transaction(
+            &[build_instruction(&[])],
+            &payer.pubkey(),
+            &[&payer, &signers[0]],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0xc"
+    );
+
+    // two of three signers claim.
+    rpc.create_and_send_transaction(
+        &[build_instruction(&[signers[2].pubkey()])],
+        &payer.pubkey(),
+        &[&payer, &signers[0], &signers[2]],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_claim_with_hook_checks() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let hook = Pubkey::new_unique();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 2, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_hook(Some(&hook))
+        })
+        .await;
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+
+    // the hook can't be skipped.
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x2"
+    );
+
+    // the hook program account must be the committed hook.
+    let mut instruction = build_claim_with_hook_instruction(
+        &accounts,
+        &[],
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        hook,
+    );
+    instruction.accounts[18].pubkey = Pubkey::new_unique();
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0xd"
+    );
+}
+
+/// Program id of the claim hook test program, see `test-programs/claim-hook`.
+const CLAIM_HOOK_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("DCtmpEVmoSEVGJxgzkGrbGSXPcWmwVPXcwanQTj58BLi");
+
+#[tokio::test]
+async fn test_claim_with_hook() {
+    let config = ProgramTestConfig::new(
+        true,
+        Some(vec![
+            ("light_compressed_claim", light_compressed_claim::id()),
+            ("claim_hook", CLAIM_HOOK_PROGRAM_ID),
+        ]),
+    );
+    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 2, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_hook(Some(&CLAIM_HOOK_PROGRAM_ID))
+        })
+        .await;
+
+    // the hook program records the destination balance it observes.
+    let record = Pubkey::new_unique();
+    let rent = rpc.context.banks_client.get_rent().await.unwrap();
+    rpc.context.set_account(
+        &record,
+        &solana_sdk::account::Account {
+            lamports: rent.minimum_balance(8),
+            data: vec![0; 8],
+            owner: CLAIM_HOOK_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_claim_with_hook_instruction(
+        &accounts,
+        &[
+            AccountMeta::new_readonly(token_account.pubkey(), false),
+            AccountMeta::new(record, false),
+        ],
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        CLAIM_HOOK_PROGRAM_ID,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 2);
+
+    // the hook ran after the airdrop was decompressed.
+    let record = rpc
+        .context
+        .banks_client
+        .get_account(record)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(u64::from_le_bytes(record.data[..8].try_into().unwrap()), 2);
+}
+
+#[tokio::test]
+async fn test_attested_claim() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let attestor = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 2, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_attestor(Some(&attestor.pubkey()))
+        })
+        .await;
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let expiry_slot = rpc.get_slot().await.unwrap() + 1000;
+    let claim_instruction = |attestation: Attestation| {
+        build_claim_and_decompress_instruction(
+            &accounts,
+            input.proof,
+            input.root_index,
+            input.merkle_context,
+            input.amount,
+            None,
+            mint.pubkey(),
+            0,
+            bump_seed,
+            None,
+            None,
+            Some(attestation),
+            None,
+            None,
+            None,
+        )
+    };
+    let attest_for = |signer: &Keypair, attestation: &Attestation, airdrop_pda: &Pubkey| {
+        let message = attestation.message(&claimant.pubkey(), airdrop_pda);
+        let signature = signer.sign_message(&message);
+        build_attestation_instruction(
+            attestation,
+            &claimant.pubkey(),
+            airdrop_pda,
+            signature.as_ref().try_into().unwrap(),
+        )
+    };
+    let attest =
+        |signer: &Keypair, attestation: &Attestation| attest_for(signer, attestation, &airdrop_pda);
+    let attestation = Attestation {
+        attestor: attestor.pubkey(),
+        expiry_slot,
+    };
+
+    // the attestation must be verified in the same transaction.
+    let result = rpc
+        .create_and_send_transaction(
+            &[claim_instruction(attestation)],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0xf"
+    );
+
+    // the attestation must be signed by the attestor.
+    let result = rpc
+        .create_and_send_transaction(
+            &[
+                attest(&claimant, &attestation),
+                claim_instruction(attestation),
+            ],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 1: custom program error: 0xf"
+    );
+
+    // expired attestations are rejected.
+    let expired = Attestation {
+        expiry_slot: 0,
+        ..attestation
+    };
+    let result = rpc
+        .create_and_send_transaction(
+            &[attest(&attestor, &expired), claim_instruction(expired)],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 1: custom program error: 0x10"
+    );
+
+    rpc.create_and_send_transaction(
+        &[
+            attest(&attestor, &attestation),
+            claim_instruction(attestation),
+        ],
+        &payer.pubkey(),
+        &[&payer, &claimant],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 2);
+
+    // another campaign of the same mint and attestor, unlocking at slot 1.
+    let current_slot = rpc.get_slot().await.unwrap();
+    rpc.warp_to_slot(current_slot.max(1) + 1).unwrap();
+    let (other_airdrop_pda, other_bump_seed) =
+        AirdropSeeds::new(&claimant.pubkey(), &mint.pubkey(), 1)
+            .with_attestor(Some(&attestor.pubkey()))
+            .find_program_address();
+    let compress_ix = compress(
+        payer.pubkey(),
+        payer.pubkey(),
+        token_account.pubkey(),
+        mint.pubkey(),
+        2,
+        other_airdrop_pda,
+        state_tree,
+    )
+    .unwrap();
+    rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let other_input = get_claim_input(&mut rpc, &other_airdrop_pda, &mint.pubkey()).await;
+    let other_accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        other_airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let other_claim_instruction = build_claim_and_decompress_instruction(
+        &other_accounts,
+        other_input.proof,
+        other_input.root_index,
+        other_input.merkle_context,
+        other_input.amount,
+        None,
+        mint.pubkey(),
+        1,
+        other_bump_seed,
+        None,
+        None,
+        Some(attestation),
+        None,
+        None,
+        None,
+    );
+
+    // the attestation of the first campaign doesn't replay to it.
+    let result = rpc
+        .create_and_send_transaction(
+            &[
+                attest(&attestor, &attestation),
+                other_claim_instruction.clone(),
+            ],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 1: custom program error: 0xf"
+    );
+
+    rpc.create_and_send_transaction(
+        &[
+            attest_for(&attestor, &attestation, &other_airdrop_pda),
+            other_claim_instruction,
+        ],
+        &payer.pubkey(),
+        &[&payer, &claimant],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 2);
+}
+
+#[tokio::test]
+async fn test_denylist() {
+    let mut rpc = setup_rpc_v2().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let output_queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+    let denylist = Denylist {
+        authority: payer.pubkey(),
+        address_tree: rpc.test_accounts.v2_address_trees[0],
+    };
+    let claimant = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, output_queue, 2, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_denylist(Some(&denylist))
+        })
+        .await;
+
+    let airdrop = (
+        claimant.pubkey(),
+        airdrop_pda,
+        mint.pubkey(),
+        token_account.pubkey(),
+        bump_seed,
+    );
+    let not_listed_claim = build_denylist_claim(&mut rpc, airdrop, denylist).await;
+
+    // the address tree is committed in the airdrop PDA seeds.
+    let mut claim_with_other_tree = not_listed_claim.clone();
+    let address_tree_index = claim_with_other_tree
+        .accounts
+        .iter()
+        .position(|account| account.pubkey == denylist.address_tree)
+        .unwrap();
+    claim_with_other_tree.accounts[address_tree_index].pubkey = Pubkey::new_unique();
+    let result = rpc
+        .create_and_send_transaction(
+            &[claim_with_other_tree],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x11"
+    );
+
+    let mut packed = denylist_packed(&rpc);
+    let (proof, entry) =
+        prove_denylist_entry(&mut rpc, &mut packed, &denylist, &claimant.pubkey()).await;
+    let add_instruction = build_add_to_denylist_instruction(
+        &denylist_accounts(&rpc, denylist.authority),
+        proof,
+        claimant.pubkey(),
+        denylist.address_tree,
+        entry,
+    );
+    rpc.create_and_send_transaction(&[add_instruction], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+
+    // the address of the entry exists, its non-inclusion can't be proven.
+    let result = rpc
+        .create_and_send_transaction(&[not_listed_claim], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert!(result.is_err());
+    // the denied entry doesn't pass as removed.
+    let denied_claim = build_denylist_claim(&mut rpc, airdrop, denylist).await;
+    let result = rpc
+        .create_and_send_transaction(&[denied_claim], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert!(result.is_err());
+
+    // only the authority can remove its entries.
+    let other_authority = Keypair::new();
+    let (
+        proof,
+        DenylistEntryProof::Existing {
+            merkle_context,
+            root_index,
+        },
+    ) = prove_denylist_entry(&mut rpc, &mut packed, &denylist, &claimant.pubkey()).await
+    else {
+        panic!("Expected an existing denylist entry.");
+    };
+    let remove = |accounts: &DenylistAccounts| {
+        build_remove_from_denylist_instruction(
+            accounts,
+            proof,
+            claimant.pubkey(),
+            denylist.address_tree,
+            merkle_context,
+            root_index,
+        )
+    };
+    let remove_instruction = remove(&denylist_accounts(&rpc, other_authority.pubkey()));
+    let result = rpc
+        .create_and_send_transaction(
+            &[remove_instruction],
+            &payer.pubkey(),
+            &[&payer, &other_authority],
+        )
+        .await;
+    assert!(result.is_err());
+
+    let remove_instruction = remove(&denylist_accounts(&rpc, denylist.authority));
+    rpc.create_and_send_transaction(&[remove_instruction], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+    let removed_claim = build_denylist_claim(&mut rpc, airdrop, denylist).await;
+    rpc.create_and_send_transaction(&[removed_claim], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 2);
+}
+
+#[tokio::test]
+async fn test_claim_window() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let expiry_slot = 100;
+    let mut instructions = Vec::new();
+    for _ in 0..2 {
+        let (mint, token_account, airdrop_pda, bump_seed) =
+            setup_airdrop_with_seeds(&mut rpc, state_tree, 2, |mint| {
+                AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_expiry_slot(Some(expiry_slot))
+            })
+            .await;
+        let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+        let accounts = claim_accounts(
+            &rpc,
+            claimant.pubkey(),
+            airdrop_pda,
+            &mint.pubkey(),
+            token_account.pubkey(),
+        );
+        instructions.push((
+            build_claim_and_decompress_instruction(
+                &accounts,
+                input.proof,
+                input.root_index,
+                input.merkle_context,
+                input.amount,
+                None,
+                mint.pubkey(),
+                0,
+                bump_seed,
+                None,
+                None,
+                None,
+                None,
+                Some(expiry_slot),
+                None,
+            ),
+            token_account,
+        ));
+    }
+    let (expired_instruction, _) = instructions.pop().unwrap();
+    let (instruction, token_account) = instructions.pop().unwrap();
+
+    // the expiry slot is committed in the airdrop PDA.
+    let mut uncommitted = instruction.clone();
+    let ClaimProgramInstruction::Claim {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        claim_amount,
+        output_tree_index,
+        attestation,
+        denylist,
+        decay,
+        ..
+    } = ClaimProgramInstruction::try_from_slice(&instruction.data).unwrap()
+    else {
+        panic!("Expected a Claim instruction.");
+    };
+    uncommitted.data = borsh::to_vec(&ClaimProgramInstruction::Claim {
+        proof,
+        root_index,
+        merkle_context,
+        amount,
+        lamports,
+        mint,
+        unlock_slot,
+        bump_seed,
+        fee,
+        claim_amount,
+        output_tree_index,
+        attestation,
+        denylist,
+        expiry_slot: None,
+        decay,
+    })
+    .unwrap();
+    let result = rpc
+        .create_and_send_transaction(&[uncommitted], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x2"
+    );
+
+    // within the claim window.
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 2);
+
+    // the window closes at the expiry slot.
+    rpc.warp_to_slot(expiry_slot).unwrap();
+    let result = rpc
+        .create_and_send_transaction(
+            &[expired_instruction],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0x13"
+    );
+}
+
+#[tokio::test]
+async fn test_early_claim() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let early_unlock = EarlyUnlock {
+        treasury: Pubkey::new_unique(),
+        penalty_bps: 5_000,
+        start_slot: 0,
+    };
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 100, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, unlock_slot)
+                .with_early_unlock(Some(&early_unlock))
+        })
+        .await;
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_early_claim_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        None,
+        early_unlock,
+    );
+
+    // half way to the unlock slot, half of the 50% penalty applies.
+    rpc.warp_to_slot(500).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 75);
+    let penalty = get_claim_input(&mut rpc, &early_unlock.treasury, &mint.pubkey()).await;
+    assert_eq!(penalty.amount, 25);
+}
+
+#[tokio::test]
+async fn test_claim_with_decay() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let decay = ClaimDecay {
+        treasury: Pubkey::new_unique(),
+        decay_start_slot: 100,
+        end_slot: 300,
+    };
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 100, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_decay(Some(&decay))
+        })
+        .await;
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(decay),
+    );
+
+    // half way through the decay, half of the airdrop is forfeited.
+    rpc.warp_to_slot(200).unwrap();
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 50);
+    let forfeited = get_claim_input(&mut rpc, &decay.treasury, &mint.pubkey()).await;
+    assert_eq!(forfeited.amount, 50);
+}
+
+#[tokio::test]
+async fn test_claim_basket() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    // the second airdrop has an expiry slot term.
+    let claimant = Keypair::new();
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let all_terms = [
+        AirdropTerms::default(),
+        AirdropTerms {
+            expiry_slot: Some(1_000),
+            ..AirdropTerms::default()
+        },
+    ];
+    let mut airdrops = Vec::new();
+    for (amount, terms) in [3, 4].into_iter().zip(all_terms) {
+        airdrops.push(
+            setup_airdrop_with_seeds(&mut rpc, state_tree, amount, |mint| {
+                AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_terms(&terms)
+            })
+            .await,
+        );
+    }
+    let mut inputs = Vec::new();
+    for (mint, _, airdrop_pda, _) in &airdrops {
+        let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+            mint: Some(mint.pubkey()),
+            cursor: None,
+            limit: None,
+        });
+        inputs.push(
+            rpc.get_compressed_token_accounts_by_owner(airdrop_pda, options, None)
+                .await
+                .unwrap()
+                .value
+                .items[0]
+                .clone(),
+        );
+    }
+    // one validity proof covers the inputs of all mints.
+    let proof = rpc
+        .indexer()
+        .unwrap()
+        .get_validity_proof(
+            inputs.iter().map(|input| input.account.hash).collect(),
+            vec![],
+            None,
+        )
+        .await
+        .unwrap();
+    let root_indices = proof.value.get_root_indices();
+    let claims: Vec<BasketClaim> = airdrops
+        .iter()
+        .zip(&inputs)
+        .zip(root_indices)
+        .zip(all_terms)
+        .map(
+            |((((mint, _, _, bump_seed), input), root_index), terms)| BasketClaim {
+                // inputs proven by index have no root.
+                root_index: root_index.unwrap_or_default(),
+                merkle_context: PackedMerkleContext {
+                    merkle_tree_pubkey_index: 0,
+                    queue_pubkey_index: 1,
+                    leaf_index: input.account.leaf_index,
+                    prove_by_index: input.account.prove_by_index,
+                },
+                amount: input.token.amount,
+                lamports: None,
+                mint: mint.pubkey(),
+                bump_seed: *bump_seed,
+                terms,
+            },
+        )
+        .collect();
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        Pubkey::default(),
+        &claims[0].mint,
+        Pubkey::default(),
+    );
+    let decompress_destinations: Vec<Pubkey> = airdrops
+        .iter()
+        .map(|(_, token_account, _, _)| token_account.pubkey())
+        .collect();
+
+    let cpi_context = rpc.test_accounts.v1_state_trees[0].cpi_context;
+    let basket = |claims| {
+        build_claim_basket_instruction(
+            &accounts,
+            proof.value.proof.clone().into(),
+            0,
+            claims,
+            cpi_context,
+            &decompress_destinations,
+        )
+    };
+
+    // hook, attestor and denylist terms are rejected.
+    let mut hook_claims = claims.clone();
+    hook_claims[1].terms.hook = Some(CLAIM_HOOK_PROGRAM_ID);
+    let result = rpc
+        .create_and_send_transaction(
+            &[basket(hook_claims)],
+            &payer.pubkey(),
+            &[&payer, &claimant],
+        )
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: invalid program argument"
+    );
+
+    let instruction = basket(claims);
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &airdrops[0].1).await, 3);
+    assert_eq!(get_token_balance(&mut rpc, &airdrops[1].1).await, 4);
+}
+
+#[tokio::test]
+async fn test_claim_lamports() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let decompress_destination = Pubkey::new_unique();
+    let (airdrop_pda, bump_seed) = find_lamports_airdrop_pda(&claimant.pubkey(), 0);
+    let accounts =
+        claim_lamports_accounts(&rpc, claimant.pubkey(), airdrop_pda, decompress_destination);
+    rpc.create_and_send_transaction(
+        &[build_airdrop_lamports_instruction(
+            &accounts,
+            payer.pubkey(),
+            1_000_000,
+            0,
+        )],
+        &payer.pubkey(),
+        &[&payer],
+    )
+    .await
+    .unwrap();
+
+    let compressed_account = rpc
+        .get_compressed_accounts_by_owner(&airdrop_pda, None, None)
+        .await
+        .unwrap()
+        .value
+        .items[0]
+        .clone();
+    let proof = rpc
+        .indexer()
+        .unwrap()
+        .get_validity_proof(vec![compressed_account.hash], vec![], None)
+        .await
+        .unwrap();
+    let instruction = build_claim_lamports_instruction(
+        &accounts,
+        proof.value.proof.clone().into(),
+        proof.value.get_root_indices()[0].unwrap_or_default(),
+        PackedMerkleContext {
+            merkle_tree_pubkey_index: 0,
+            queue_pubkey_index: 1,
+            leaf_index: compressed_account.leaf_index,
+            prove_by_index: compressed_account.prove_by_index,
+        },
+        compressed_account.lamports,
+        0,
+        bump_seed,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    let decompressed = rpc
+        .get_account(decompress_destination)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(decompressed.lamports, 1_000_000);
+}
+
+#[tokio::test]
+async fn test_partial_claim() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), 0, 10, None).await;
+    let accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+
+    // claim 4, the remaining 6 stay in the airdrop PDA.
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        Some(4),
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 4);
+
+    // claiming more than the remainder fails.
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    assert_eq!(input.amount, 6);
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        Some(7),
+        None,
+        None,
+        None,
+        None,
+    );
+    let result = rpc
+        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await;
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "TransactionError: Error processing Instruction 0: custom program error: 0xe"
+    );
+
+    // claim the remainder.
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_claim_from_batched_state_tree() {
+    let mut rpc = setup_rpc_v2().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let merkle_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let output_queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+    let claimant = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, output_queue, 10, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0)
+        })
+        .await;
+    let mut accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    accounts.tree_accounts = vec![merkle_tree, output_queue];
+    accounts.output_tree_index = 1;
+
+    // the airdrop is still in the output queue and is proven by index.
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    assert!(input.merkle_context.prove_by_index);
+    assert!(input.proof.is_none());
+
+    // the change is written to the output queue.
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        Some(4),
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 4);
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    assert_eq!(input.amount, 6);
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_claim_change_to_other_tree() {
+    let mut rpc = setup_rpc_v2().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let merkle_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let output_queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+    let claimant = Keypair::new();
+    let (mint, token_account, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), 0, 10, None).await;
+
+    // the input is in the v1 tree, the change goes to the batched output queue.
+    let mut accounts = claim_accounts(
+        &rpc,
+        claimant.pubkey(),
+        airdrop_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    assert!(!input.merkle_context.prove_by_index);
+    let mut packed = PackedClaimAccounts::new();
+    let merkle_context = packed.pack_merkle_context(
+        rpc.test_accounts.v1_state_trees[0].merkle_tree,
+        rpc.test_accounts.v1_state_trees[0].nullifier_queue,
+        input.merkle_context.leaf_index,
+        input.merkle_context.prove_by_index,
+    );
+    accounts.output_tree_index = packed.pack_output_tree(output_queue);
+    accounts.tree_accounts = packed.into_tree_accounts();
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        Some(4),
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 4);
+
+    let input = get_claim_input(&mut rpc, &airdrop_pda, &mint.pubkey()).await;
+    assert_eq!(input.amount, 6);
+    assert!(input.merkle_context.prove_by_index);
+    let mut packed = PackedClaimAccounts::new();
+    let merkle_context = packed.pack_merkle_context(
+        merkle_tree,
+        output_queue,
+        input.merkle_context.leaf_index,
+        input.merkle_context.prove_by_index,
+    );
+    accounts.output_tree_index = packed.pack_output_tree(output_queue);
+    accounts.tree_accounts = packed.into_tree_accounts();
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        0,
+        bump_seed,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut rpc, &token_account).await, 10);
+}
+
+#[tokio::test]
+async fn test_get_claimable() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let claimant = Keypair::new();
+    let unlock_slot = 1_000;
+    let fee = ClaimFee {
+        amount: FeeAmount::Bps(1_000),
+        recipient: Pubkey::new_unique(),
+    };
+    let (mint, _, airdrop_pda, bump_seed) =
+        setup_airdrop(&mut rpc, &claimant.pubkey(), unlock_slot, 10, Some(fee)).await;
+    let source = ClaimSource::Airdrop {
+        claimant: claimant.pubkey(),
+        mint: mint.pubkey(),
+        unlock_slot,
+        fee: Some(fee),
+        hook: None,
+        attestor: None,
+        denylist: None,
+        expiry_slot: None,
+        early_unlock: None,
+        decay: None,
+    };
+    let instruction = build_get_claimable_instruction(airdrop_pda, source.clone(), 10, bump_seed);
+
+    assert_eq!(
+        simulate_get_claimable(&mut rpc, instruction.clone()).await,
+        Claimable {
+            amount: 0,
+            next_unlock_slot: Some(unlock_slot),
+            paused: false,
+        }
+    );
+    rpc.warp_to_slot(unlock_slot).unwrap();
+    assert_eq!(
+        simulate_get_claimable(&mut rpc, instruction).await,
+        Claimable {
+            amount: 9,
+            next_unlock_slot: None,
+            paused: false,
+        }
+    );
+
+    // the airdrop PDA must match the source.
+    let instruction = build_get_claimable_instruction(Pubkey::new_unique(), source, 10, bump_seed);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        rpc.context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap(),
+    );
+    let result = rpc
+        .context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(result.result.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn test_get_claimable_decay_and_expiry() {
+    let mut rpc = setup_rpc().await;
+    let payer = rpc.get_payer().insecure_clone();
+    initialize_config(&mut rpc, payer.pubkey()).await;
+
+    let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
+    let claimant = Keypair::new();
+    let current_slot = rpc.get_slot().await.unwrap();
+    let decay = ClaimDecay {
+        treasury: Pubkey::new_unique(),
+        decay_start_slot: current_slot + 100,
+        end_slot: current_slot + 200,
+    };
+    let expiry_slot = current_slot + 300;
+    let (decaying_mint, _, decaying_pda, decaying_bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 10, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_decay(Some(&decay))
+        })
+        .await;
+    let (expiring_mint, _, expiring_pda, expiring_bump_seed) =
+        setup_airdrop_with_seeds(&mut rpc, state_tree, 10, |mint| {
+            AirdropSeeds::new(&claimant.pubkey(), mint, 0).with_expiry_slot(Some(expiry_slot))
+        })
+        .await;
+    let airdrop = |mint: &Keypair, expiry_slot, decay| ClaimSource::Airdrop {
+        claimant: claimant.pubkey(),
+        mint: mint.pubkey(),
+        unlock_slot: 0,
+        fee: None,
+        hook: None,
+        attestor: None,
+        denylist: None,
+        expiry_slot,
+        early_unlock: None,
+        decay,
+    };
+    let decaying = build_get_claimable_instruction(
+        decaying_pda,
+        airdrop(&decaying_mint, None, Some(decay)),
+        10,
+        decaying_bump_seed,
+    );
+    let expiring = build_get_claimable_instruction(
+        expiring_pda,
+        airdrop(&expiring_mint, Some(expiry_slot), None),
+        10,
+        expiring_bump_seed,
+    );
+    let expired = Claimable {
+        amount: 0,
+        next_unlock_slot: None,
+        paused: false,
+    };
+
+    // halfway through the decay, half of the airdrop is forfeited.
+    rpc.warp_to_slot(current_slot + 150).unwrap();
+    assert_eq!(
+        simulate_get_claimable(&mut rpc, decaying.clone()).await,
+        Claimable {
+            amount: 5,
+            next_unlock_slot: None,
+            paused: false,
+        }
+    );
+    assert_eq!(
+        simulate_get_claimable(&mut rpc, expiring.clone())
+            .await
+            .amount,
+        10
+    );
+
+    // nothing is claimable once the decay ends or the claim window closes.
+    rpc.warp_to_slot(decay.end_slot).unwrap();
+    assert_eq!(simulate_get_claimable(&mut rpc, decaying).await, expired);
+    rpc.warp_to_slot(expiry_slot).unwrap();
+    assert_eq!(simulate_get_claimable(&mut rpc, expiring).await, expired);
+}
+
+/// Simulates a `GetClaimable` instruction and decodes its return data.
+pub async fn simulate_get_claimable(
+    rpc: &mut LightProgramTest,
+    instruction: Instruction,
+) -> Claimable {
+    let payer = rpc.get_payer().insecure_clone();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        rpc.context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap(),
+    );
+    let result = rpc
+        .context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    let data = result
+        .simulation_details
+        .unwrap()
+        .return_data
+        .map(|return_data| return_data.data)
+        .unwrap_or_default();
+    Claimable::try_from_return_data(&data).unwrap()
+}
+
+pub async fn setup_rpc() -> LightProgramTest {
     let config = ProgramTestConfig::new(
         true,
         Some(vec![(
@@ -30,20 +2731,133 @@ async fn test_claim_and_decompress() {
             light_compressed_claim::id(),
         )]),
     );
-    let mut rpc = LightProgramTest::new(config).await.unwrap();
+    LightProgramTest::new(config).await.unwrap()
+}
+
+/// Test rpc with batched (v2) state trees.
+pub async fn setup_rpc_v2() -> LightProgramTest {
+    let config = ProgramTestConfig::new_v2(
+        true,
+        Some(vec![(
+            "light_compressed_claim",
+            light_compressed_claim::id(),
+        )]),
+    );
+    LightProgramTest::new(config).await.unwrap()
+}
+
+pub async fn initialize_config(rpc: &mut LightProgramTest, admin: Pubkey) {
+    let payer = rpc.get_payer().insecure_clone();
+    set_upgrade_authority(rpc, &payer.pubkey()).await;
+    let instruction = build_initialize_config_instruction(payer.pubkey(), admin);
+    rpc.create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer])
+        .await
+        .unwrap();
+}
+
+/// Makes `upgrade_authority` the upgrade authority of the claim program. The
+/// test validator loads programs with the non-upgradeable loader, so the
+/// program data account only holds the loader state.
+pub async fn set_upgrade_authority(rpc: &mut LightProgramTest, upgrade_authority: &Pubkey) {
+    // bincode `UpgradeableLoaderState::ProgramData` without program bytes.
+    let data = [
+        &[3u8, 0, 0, 0][..],
+        &0u64.to_le_bytes(),
+        &[1],
+        upgrade_authority.as_ref(),
+    ]
+    .concat();
+    let rent = rpc.context.banks_client.get_rent().await.unwrap();
+    let program_data = solana_sdk::account::Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    rpc.context.set_account(
+        &bpf_loader_upgradeable::get_program_data_address(&light_compressed_claim::id()),
+        &program_data.into(),
+    );
+}
+
+/// Compresses `amount` tokens of a new mint to the airdrop PDA of `claimant`
+/// and builds the instruction that claims them into the original token account.
+///
+/// Returns (claim_instruction, token_account)
+pub async fn setup_airdrop_claim(
+    rpc: &mut LightProgramTest,
+    claimant: &Keypair,
+    unlock_slot: u64,
+    amount: u64,
+    fee: Option<ClaimFee>,
+) -> (Instruction, Keypair) {
+    let (mint, token_account, claimant_pda, bump_seed) =
+        setup_airdrop(rpc, &claimant.pubkey(), unlock_slot, amount, fee).await;
+
+    let input = get_claim_input(rpc, &claimant_pda, &mint.pubkey()).await;
+    let mut accounts = claim_accounts(
+        rpc,
+        claimant.pubkey(),
+        claimant_pda,
+        &mint.pubkey(),
+        token_account.pubkey(),
+    );
+    accounts.fee_recipient = fee.map(|fee| fee.recipient);
+
+    let instruction = build_claim_and_decompress_instruction(
+        &accounts,
+        input.proof,
+        input.root_index,
+        input.merkle_context,
+        input.amount,
+        None,
+        mint.pubkey(),
+        unlock_slot,
+        bump_seed,
+        fee,
+        None,
+        None,
+        None,
+        None,
+        None,
+    );
+    (instruction, token_account)
+}
+
+/// Compresses `amount` tokens of a new mint to the airdrop PDA of `claimant`.
+///
+/// Returns (mint, token_account, airdrop_pda, bump_seed)
+pub async fn setup_airdrop(
+    rpc: &mut LightProgramTest,
+    claimant: &Pubkey,
+    unlock_slot: u64,
+    amount: u64,
+    fee: Option<ClaimFee>,
+) -> (Keypair, Keypair, Pubkey, u8) {
     let state_tree = rpc.test_accounts.v1_state_trees[0].merkle_tree;
-    let queue = rpc.test_accounts.v1_state_trees[0].nullifier_queue;
+    setup_airdrop_with_seeds(rpc, state_tree, amount, |mint| {
+        AirdropSeeds::new(claimant, mint, unlock_slot).with_fee(fee.as_ref())
+    })
+    .await
+}
 
-    let (mint, token_account, owner) = setup_spl_token_account(&mut rpc).await;
-    setup_token_pool(&mut rpc, &mint).await;
+/// Compresses `amount` tokens of a new mint to the airdrop PDA derived from
+/// `seeds(mint)`. `state_tree` is a v1 state tree or a batched output queue.
+///
+/// Returns (mint, token_account, airdrop_pda, bump_seed)
+pub async fn setup_airdrop_with_seeds(
+    rpc: &mut LightProgramTest,
+    state_tree: Pubkey,
+    amount: u64,
+    seeds: impl FnOnce(&Pubkey) -> AirdropSeeds,
+) -> (Keypair, Keypair, Pubkey, u8) {
+    let (mint, token_account, owner) = setup_spl_token_account(rpc).await;
+    setup_token_pool(rpc, &mint).await;
 
     let payer = rpc.get_payer().insecure_clone();
-    let claimant = Keypair::new();
-    let unlock_slot = 1_000;
-    let amount = 2;
 
-    let (claimant_pda, bump_seed) =
-        find_claimant_pda(claimant.pubkey(), mint.pubkey(), unlock_slot);
+    let (claimant_pda, bump_seed) = seeds(&mint.pubkey()).find_program_address();
 
     let compress_ix = compress(
         payer.pubkey(),
@@ -56,19 +2870,35 @@ async fn test_claim_and_decompress() {
     )
     .unwrap();
 
-    // we compress 2 tokens to the timelocked recipient PDA.
+    // we compress the tokens to the timelocked recipient PDA.
     rpc.create_and_send_transaction(&[compress_ix], &payer.pubkey(), &[&payer, &owner])
         .await
         .unwrap();
 
-    // Fetch compressed token account and validity proof.
+    (mint, token_account, claimant_pda, bump_seed)
+}
+
+pub struct ClaimInput {
+    pub proof: Option<CompressedProof>,
+    pub root_index: u16,
+    pub merkle_context: PackedMerkleContext,
+    pub amount: u64,
+}
+
+/// Fetches the first compressed token account of `owner` for `mint` and its
+/// validity proof.
+pub async fn get_claim_input(
+    rpc: &mut LightProgramTest,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> ClaimInput {
     let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
-        mint: Some(mint.pubkey()),
+        mint: Some(*mint),
         cursor: None,
         limit: None,
     });
     let compressed_token_account = rpc
-        .get_compressed_token_accounts_by_owner(&claimant_pda, options, None)
+        .get_compressed_token_accounts_by_owner(owner, options, None)
         .await
         .unwrap()
         .value
@@ -82,12 +2912,86 @@ async fn test_claim_and_decompress() {
         .await
         .unwrap();
 
+    ClaimInput {
+        proof: proof.value.proof.clone().into(),
+        // inputs proven by index have no root.
+        root_index: proof.value.get_root_indices()[0].unwrap_or_default(),
+        merkle_context: PackedMerkleContext {
+            merkle_tree_pubkey_index: 0,
+            queue_pubkey_index: 1,
+            leaf_index: compressed_token_account.account.leaf_index,
+            prove_by_index: compressed_token_account.account.prove_by_index,
+        },
+        amount: compressed_token_account.token.amount,
+    }
+}
+
+/// Sums the compressed token accounts of `owner` for `mint`.
+pub async fn get_compressed_balance(
+    rpc: &mut LightProgramTest,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> u64 {
+    let options = Some(GetCompressedTokenAccountsByOwnerOrDelegateOptions {
+        mint: Some(*mint),
+        cursor: None,
+        limit: None,
+    });
+    rpc.get_compressed_token_accounts_by_owner(owner, options, None)
+        .await
+        .unwrap()
+        .value
+        .items
+        .iter()
+        .map(|account| account.token.amount)
+        .sum()
+}
+
+/// Lamports claim accounts for the first v1 state tree.
+pub fn claim_lamports_accounts(
+    rpc: &LightProgramTest,
+    claimant: Pubkey,
+    associated_airdrop_pda: Pubkey,
+    decompress_destination: Pubkey,
+) -> ClaimLamportsAccounts {
+    let accounts = claim_accounts(
+        rpc,
+        claimant,
+        associated_airdrop_pda,
+        &Pubkey::default(),
+        decompress_destination,
+    );
+    ClaimLamportsAccounts {
+        claimant,
+        fee_payer: accounts.fee_payer,
+        associated_airdrop_pda,
+        light_system_program: accounts.light_system_program,
+        registered_program_pda: accounts.registered_program_pda,
+        noop_program: accounts.noop_program,
+        account_compression_authority: accounts.account_compression_authority,
+        account_compression_program: accounts.account_compression_program,
+        sol_pool_pda: Pubkey::find_program_address(&[b"sol_pool_pda"], &LIGHT_SYSTEM_PROGRAM_ID).0,
+        decompress_destination,
+        system_program: accounts.system_program,
+        config: accounts.config,
+        tree_accounts: accounts.tree_accounts,
+    }
+}
+
+/// Claim accounts for the first v1 state tree, without fee recipient.
+/// Inputs are in tree account 0 with queue 1, outputs go to tree account 0.
+pub fn claim_accounts(
+    rpc: &LightProgramTest,
+    claimant: Pubkey,
+    associated_airdrop_pda: Pubkey,
+    mint: &Pubkey,
+    decompress_destination: Pubkey,
+) -> ClaimAccounts {
     // TODO: provide helper.
-    let token_pool_pda = get_token_pool_pda(&mint.pubkey());
-    let accounts = ClaimAccounts {
-        claimant: claimant.pubkey(),
-        fee_payer: payer.pubkey(),
-        associated_airdrop_pda: claimant_pda,
+    ClaimAccounts {
+        claimant,
+        fee_payer: rpc.get_payer().pubkey(),
+        associated_airdrop_pda,
         ctoken_cpi_authority_pda: Pubkey::from_str_const(
             "GXtd2izAiMJPwMEjfgTRH3d7k9mjn4Jq3JrWFv9gySYy",
         ),
@@ -103,77 +3007,175 @@ async fn test_claim_and_decompress() {
         .0,
         account_compression_program: ACCOUNT_COMPRESSION_PROGRAM_ID,
         ctoken_program: Pubkey::from_str_const("cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m"),
-        token_pool_pda,
-        decompress_destination: token_account.pubkey(),
+        token_pool_pda: get_token_pool_pda(mint),
+        decompress_destination,
         token_program: spl_token::ID,
         system_program: solana_sdk::system_program::ID,
-        state_tree,
-        queue,
-    };
+        config: find_config_pda().0,
+        fee_recipient: None,
+        tree_accounts: vec![
+            rpc.test_accounts.v1_state_trees[0].merkle_tree,
+            rpc.test_accounts.v1_state_trees[0].nullifier_queue,
+        ],
+        output_tree_index: 0,
+    }
+}
 
-    let packed_merkle_context = PackedMerkleContext {
-        merkle_tree_pubkey_index: 0,
-        queue_pubkey_index: 1,
-        leaf_index: compressed_token_account.account.leaf_index,
-        prove_by_index: compressed_token_account.account.prove_by_index,
-    };
+/// Tree accounts of denylist instructions: the output queue and tree of the
+/// first v2 state tree and the first v2 address tree. Entries are written to
+/// the output queue at index 0.
+pub fn denylist_packed(rpc: &LightProgramTest) -> PackedClaimAccounts {
+    let mut packed = PackedClaimAccounts::new();
+    packed.pack_output_tree(rpc.test_accounts.v2_state_trees[0].output_queue);
+    packed.insert_or_get(rpc.test_accounts.v2_state_trees[0].merkle_tree);
+    packed.insert_or_get(rpc.test_accounts.v2_address_trees[0]);
+    packed
+}
 
-    let instruction = build_claim_and_decompress_instruction(
-        &accounts,
-        proof.value.proof.clone().into(),
-        proof.value.get_root_indices()[0].unwrap(),
-        packed_merkle_context,
-        amount,
-        None,
-        mint.pubkey(),
-        unlock_slot,
-        bump_seed,
+/// Denylist accounts of `authority` with the tree accounts of
+/// `denylist_packed`.
+pub fn denylist_accounts(rpc: &LightProgramTest, authority: Pubkey) -> DenylistAccounts {
+    let accounts = claim_accounts(
+        rpc,
+        authority,
+        Pubkey::default(),
+        &Pubkey::default(),
+        Pubkey::default(),
     );
-    let instruction_clone = instruction.clone();
-
-    // SPL token account should be without the compressed tokens.
-    let account_info = rpc
-        .context
-        .banks_client
-        .get_account(token_account.pubkey())
-        .await
-        .unwrap();
-    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
-    assert_eq!(account_data.amount, 10 - amount);
+    DenylistAccounts {
+        authority,
+        registered_program_pda: accounts.registered_program_pda,
+        noop_program: accounts.noop_program,
+        account_compression_authority: accounts.account_compression_authority,
+        account_compression_program: accounts.account_compression_program,
+        tree_accounts: denylist_packed(rpc).into_tree_accounts(),
+        output_tree_index: 0,
+    }
+}
 
-    // not yet unlocked.
-    rpc.warp_to_slot(999).unwrap();
-    let result = rpc
-        .create_and_send_transaction(&[instruction], &payer.pubkey(), &[&payer, &claimant])
-        .await;
-    assert_eq!(
-        result.unwrap_err().to_string(),
-        "TransactionError: Error processing Instruction 0: custom program error: 0x1"
+/// Builds a claim of `airdrop`, as (claimant, airdrop PDA, mint, decompress
+/// destination, bump seed), in the first v2 state tree that commits to
+/// `denylist`. The token input is proven by index, the validity proof covers
+/// the denylist entry of the claimant.
+pub async fn build_denylist_claim(
+    rpc: &mut LightProgramTest,
+    airdrop: (Pubkey, Pubkey, Pubkey, Pubkey, u8),
+    denylist: Denylist,
+) -> Instruction {
+    let (claimant, airdrop_pda, mint, decompress_destination, bump_seed) = airdrop;
+    let merkle_tree = rpc.test_accounts.v2_state_trees[0].merkle_tree;
+    let output_queue = rpc.test_accounts.v2_state_trees[0].output_queue;
+    let input = get_claim_input(rpc, &airdrop_pda, &mint).await;
+    let mut packed = PackedClaimAccounts::new();
+    let merkle_context = packed.pack_merkle_context(
+        merkle_tree,
+        output_queue,
+        input.merkle_context.leaf_index,
+        input.merkle_context.prove_by_index,
     );
+    let output_tree_index = packed.pack_output_tree(output_queue);
+    let cpi_context_index = packed.insert_or_get(rpc.test_accounts.v2_state_trees[0].cpi_context);
+    let (proof, entry) = prove_denylist_entry(rpc, &mut packed, &denylist, &claimant).await;
 
-    // now unlocked.
-    rpc.warp_to_slot(1000).unwrap();
-    rpc.create_and_send_transaction(&[instruction_clone], &payer.pubkey(), &[&payer, &claimant])
+    let mut accounts = claim_accounts(rpc, claimant, airdrop_pda, &mint, decompress_destination);
+    accounts.tree_accounts = packed.into_tree_accounts();
+    accounts.output_tree_index = output_tree_index;
+    build_claim_and_decompress_instruction(
+        &accounts,
+        proof,
+        input.root_index,
+        merkle_context,
+        input.amount,
+        None,
+        mint,
+        0,
+        bump_seed,
+        None,
+        None,
+        None,
+        Some(DenylistCheck {
+            denylist,
+            entry,
+            cpi_context_index,
+        }),
+        None,
+        None,
+    )
+}
+
+/// Fetches the denylist entry of `address` in `denylist` and proves it: its
+/// inclusion if it exists, otherwise the non-inclusion of its address. The
+/// trees of the entry are packed into `packed`.
+pub async fn prove_denylist_entry(
+    rpc: &mut LightProgramTest,
+    packed: &mut PackedClaimAccounts,
+    denylist: &Denylist,
+    address: &Pubkey,
+) -> (Option<CompressedProof>, DenylistEntryProof) {
+    let entry_address = derive_denylist_entry_address(denylist, address);
+    let entry = rpc
+        .indexer()
+        .unwrap()
+        .get_compressed_account(entry_address, None)
         .await
-        .unwrap();
+        .ok()
+        .map(|response| response.value);
+    match entry {
+        Some(entry) => {
+            let proof = rpc
+                .indexer()
+                .unwrap()
+                .get_validity_proof(vec![entry.hash], vec![], None)
+                .await
+                .unwrap();
+            let merkle_context = packed.pack_merkle_context(
+                entry.tree_info.tree,
+                entry.tree_info.queue,
+                entry.leaf_index,
+                entry.prove_by_index,
+            );
+            (
+                proof.value.proof.clone().into(),
+                DenylistEntryProof::Existing {
+                    merkle_context,
+                    // inputs proven by index have no root.
+                    root_index: proof.value.get_root_indices()[0].unwrap_or_default(),
+                },
+            )
+        }
+        None => {
+            let proof = rpc
+                .indexer()
+                .unwrap()
+                .get_validity_proof(
+                    vec![],
+                    vec![AddressWithTree {
+                        address: entry_address,
+                        tree: denylist.address_tree,
+                    }],
+                    None,
+                )
+                .await
+                .unwrap();
+            (
+                proof.value.proof.clone().into(),
+                DenylistEntryProof::Absent {
+                    address_tree_index: packed.insert_or_get(denylist.address_tree),
+                    address_root_index: proof.value.get_address_root_indices()[0],
+                },
+            )
+        }
+    }
+}
 
+pub async fn get_token_balance(rpc: &mut LightProgramTest, token_account: &Keypair) -> u64 {
     let account_info = rpc
         .context
         .banks_client
         .get_account(token_account.pubkey())
         .await
         .unwrap();
-    let account_data = Account::unpack(&account_info.unwrap().data).unwrap();
-    assert_eq!(account_data.amount, 10);
-}
-
-pub fn find_claimant_pda(claimant: Pubkey, mint: Pubkey, slot: u64) -> (Pubkey, u8) {
-    let claimant_bytes = claimant.to_bytes();
-    let mint_bytes = mint.to_bytes();
-    let slot_bytes = slot.to_le_bytes();
-    let seeds = &[&claimant_bytes[..32], &mint_bytes[..32], &slot_bytes[..8]];
-
-    Pubkey::find_program_address(seeds, &light_compressed_claim::id())
+    Account::unpack(&account_info.unwrap().data).unwrap().amount
 }
 
 pub async fn setup_token_pool(rpc: &mut LightProgramTest, mint: &Keypair) {
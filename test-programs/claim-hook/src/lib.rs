@@ -0,0 +1,61 @@
+//! Claim hook program of the functional tests.
+//!
+//! Records the balance of the decompress destination when it is invoked, so
+//! tests can check that the hook runs after the airdrop is decompressed.
+//!
+//! Accounts:
+//! 0. `[]` decompress destination token account
+//! 1. `[writable]` record account owned by this program, 8 bytes
+use borsh::BorshDeserialize;
+use light_compressed_claim::hook::{ClaimHookData, CLAIM_HOOK_DISCRIMINATOR};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Account;
+
+solana_program::declare_id!("DCtmpEVmoSEVGJxgzkGrbGSXPcWmwVPXcwanQTj58BLi");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let data = instruction_data
+        .strip_prefix(&CLAIM_HOOK_DISCRIMINATOR)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let data = ClaimHookData::try_from_slice(data)?;
+
+    let account_info_iter = &mut accounts.iter();
+    let destination = next_account_info(account_info_iter)?;
+    let record = next_account_info(account_info_iter)?;
+    // CHECK: the destination is the decompress destination of the claim.
+    if *destination.key != data.decompress_destination {
+        msg!("Destination is not the decompress destination.");
+        return Err(ProgramError::InvalidArgument);
+    }
+    // CHECK: the record is owned by this program.
+    if record.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let balance = Account::unpack(&destination.try_borrow_data()?)?.amount;
+    msg!(
+        "Claimed {} tokens, destination balance {}.",
+        data.amount,
+        balance
+    );
+    record
+        .try_borrow_mut_data()?
+        .get_mut(..8)
+        .ok_or(ProgramError::AccountDataTooSmall)?
+        .copy_from_slice(&balance.to_le_bytes());
+    Ok(())
+}